@@ -0,0 +1,293 @@
+use handlegraph::{
+    handle::{Edge, Handle, NodeId},
+    handlegraph::*,
+    mutablehandlegraph::*,
+    pathhandlegraph::*,
+};
+
+use handlegraph::packedgraph::PackedGraph;
+
+use anyhow::{bail, Result};
+
+use fxhash::FxHashMap;
+
+use crate::io::LoadedGraph;
+
+/// Turns a graph with overlapping edges into a blunt graph that
+/// spells the same sequences. Each overlap is removed by trimming the
+/// overlapping bases from the node side at one end of the edge; the
+/// node sides to trim are picked so that no overlap is trimmed twice.
+///
+/// This only works when every overlap is an ungapped alignment and
+/// all edges on a node side have the same overlap, which holds for
+/// de Bruijn and most string graphs. Otherwise, or if a node would be
+/// trimmed away completely, an error is returned.
+///
+/// Node IDs, tags, paths, and jumps are kept, as are containments
+/// that don't involve a trimmed node. Trimmed nodes lose their `LN`
+/// tag, so placeholders are written with their trimmed length. A path that starts or ends on
+/// a trimmed node side loses the trimmed bases, which is reported.
+pub fn bluntify(loaded: LoadedGraph) -> Result<LoadedGraph> {
+    if loaded.overlaps.is_empty() {
+        return Ok(loaded);
+    }
+
+    let graph = &loaded.graph;
+
+    for (edge, overlap) in loaded.overlaps.iter() {
+        if !overlap.is_ungapped() {
+            let Edge(left, right) = edge;
+            bail!(
+                "can't bluntify the edge from {} to {}, overlap {} has gaps",
+                u64::from(left.id()),
+                u64::from(right.id()),
+                overlap
+            );
+        }
+    }
+
+    // A node side is the end of a handle, so the edge `Edge(l, r)`
+    // connects the sides `l` and `r.flip()`. Every edge on a side
+    // needs the same overlap, blunt edges counting as 0.
+    let mut side_overlaps: FxHashMap<Handle, usize> = FxHashMap::default();
+    let mut neighbors: FxHashMap<Handle, Vec<Handle>> = FxHashMap::default();
+    let mut mismatched = 0;
+
+    for edge in graph.edges() {
+        let Edge(left, right) = edge;
+        let overlap = loaded.overlaps.get(edge);
+        let len = overlap.as_ref().map(|o| o.from_len()).unwrap_or(0);
+
+        for &side in [left, right.flip()].iter() {
+            let side_len = *side_overlaps.entry(side).or_insert(len);
+            if side_len != len {
+                bail!(
+                    "can't bluntify node {}, it has edges with overlaps of \
+                     {} and {} bp on the same side",
+                    u64::from(side.id()),
+                    side_len,
+                    len
+                );
+            }
+        }
+
+        if len == 0 {
+            continue;
+        }
+
+        if left == right.flip() {
+            bail!(
+                "can't bluntify the overlapping edge from node {} to its \
+                 own reverse complement",
+                u64::from(left.id())
+            );
+        }
+
+        neighbors.entry(left).or_default().push(right.flip());
+        neighbors.entry(right.flip()).or_default().push(left);
+
+        let left_seq = graph.sequence_vec(left);
+        let right_seq = graph.sequence_vec(right);
+        // the loader checks overlaps against the segment lengths, but
+        // segments without a sequence are stored as placeholders
+        let shortest = left_seq.len().min(right_seq.len());
+        if len > shortest {
+            bail!(
+                "can't bluntify the edge from {} to {}, its overlap of {} bp \
+                 is longer than the {} bp sequence of a node",
+                u64::from(left.id()),
+                u64::from(right.id()),
+                len,
+                shortest
+            );
+        }
+        if left_seq[left_seq.len() - len..] != right_seq[..len] {
+            mismatched += 1;
+        }
+    }
+
+    if mismatched > 0 {
+        eprintln!(
+            "warning: the sequences of {} overlaps don't match, the \
+             bluntified graph uses the sequence of one side",
+            mismatched
+        );
+    }
+
+    let trimmed = trimmed_sides(&neighbors)?;
+
+    // bases to trim from the start and end of each node
+    let mut trims: FxHashMap<NodeId, (usize, usize)> = FxHashMap::default();
+    for &side in trimmed.iter() {
+        let len = side_overlaps[&side];
+        let trim = trims.entry(side.id()).or_default();
+        if side.is_reverse() {
+            trim.0 = len;
+        } else {
+            trim.1 = len;
+        }
+    }
+
+    let mut handles = graph.handles().collect::<Vec<_>>();
+    handles.sort();
+
+    let mut blunt = PackedGraph::default();
+
+    for handle in handles {
+        let sequence = graph.sequence_vec(handle);
+        let (start, end) = trims.get(&handle.id()).copied().unwrap_or_default();
+        if start + end >= sequence.len() {
+            bail!(
+                "can't bluntify node {}, its overlaps of {} and {} bp cover \
+                 all of its {} bp",
+                u64::from(handle.id()),
+                start,
+                end,
+                sequence.len()
+            );
+        }
+        blunt
+            .create_handle(&sequence[start..sequence.len() - end], handle.id());
+    }
+
+    blunt.create_edges_iter(graph.edges());
+
+    let mut old_paths: FxHashMap<PathId, PathId> = FxHashMap::default();
+    let mut new_paths: FxHashMap<PathId, PathId> = FxHashMap::default();
+    let mut trimmed_paths = 0;
+
+    let mut path_ids = graph.path_ids().collect::<Vec<_>>();
+    path_ids.sort();
+
+    for path_id in path_ids {
+        let name = graph.get_path_name_vec(path_id).unwrap_or_default();
+        let circular = graph.path_circular(path_id).unwrap_or_default();
+        if let Some(new_id) = blunt.create_path(&name, circular) {
+            old_paths.insert(new_id, path_id);
+            new_paths.insert(path_id, new_id);
+        }
+
+        if !circular {
+            let mut steps = graph.path_steps(path_id).into_iter().flatten();
+            let first = steps.next().map(|step| step.handle());
+            let last = steps.last().map(|step| step.handle()).or(first);
+            let trimmed_ends = first
+                .is_some_and(|h| trimmed.binary_search(&h.flip()).is_ok())
+                || last.is_some_and(|h| trimmed.binary_search(&h).is_ok());
+            if trimmed_ends {
+                trimmed_paths += 1;
+            }
+        }
+    }
+
+    blunt.with_all_paths_mut_ctx_chn_new(|path_id, sender, path_ref| {
+        if let Some(steps) = old_paths
+            .get(&path_id)
+            .and_then(|&old_id| graph.path_steps(old_id))
+        {
+            path_ref.append_handles_iter_chn(sender, steps.map(|s| s.handle()));
+        }
+    });
+
+    if trimmed_paths > 0 {
+        eprintln!(
+            "warning: {} paths start or end on a trimmed node side, and \
+             lose the overlapping bases",
+            trimmed_paths
+        );
+    }
+
+    eprintln!(
+        "bluntified {} overlaps, trimming {} node sides",
+        loaded.overlaps.len(),
+        trimmed.len()
+    );
+
+    // the positions of containments in trimmed nodes would shift
+    let mut containments = loaded.containments;
+    let containment_count = containments.len();
+    containments.retain(|c| {
+        !trims.contains_key(&c.container.id())
+            && !trims.contains_key(&c.contained.id())
+    });
+    if containments.len() < containment_count {
+        eprintln!(
+            "warning: dropped {} containments of trimmed nodes",
+            containment_count - containments.len()
+        );
+    }
+
+    let tags = loaded.tags.map(|mut tags| {
+        tags.paths = tags
+            .paths
+            .into_iter()
+            .filter_map(|(id, fields)| Some((*new_paths.get(&id)?, fields)))
+            .collect();
+        // the lengths of trimmed segments have changed
+        for &id in trims.keys() {
+            tags.remove_length_tag(id);
+        }
+        tags
+    });
+
+    Ok(LoadedGraph {
+        graph: blunt,
+        id_mapping: loaded.id_mapping,
+        tags,
+        headers: loaded.headers,
+        rejected: loaded.rejected,
+        overlaps: Default::default(),
+        jumps: loaded.jumps,
+        containments,
+        placeholders: loaded.placeholders,
+        literal: loaded.literal,
+    })
+}
+
+/// Picks the node sides to trim, so that exactly one side of each
+/// overlapping edge is trimmed. This is a 2-colouring of the graph
+/// of node sides connected by overlaps; in each connected component,
+/// the sides with a different colour from the smallest side are
+/// trimmed.
+fn trimmed_sides(
+    neighbors: &FxHashMap<Handle, Vec<Handle>>,
+) -> Result<Vec<Handle>> {
+    let mut sides = neighbors.keys().copied().collect::<Vec<_>>();
+    sides.sort();
+
+    let mut colors: FxHashMap<Handle, bool> = FxHashMap::default();
+    let mut trimmed = Vec::new();
+    let mut stack = Vec::new();
+
+    for start in sides {
+        if colors.contains_key(&start) {
+            continue;
+        }
+        colors.insert(start, false);
+        stack.push(start);
+
+        while let Some(side) = stack.pop() {
+            let color = colors[&side];
+            if color {
+                trimmed.push(side);
+            }
+            for &other in neighbors[&side].iter() {
+                match colors.get(&other) {
+                    Some(&c) if c == color => bail!(
+                        "can't bluntify the overlaps around node {}, they \
+                         form an odd cycle",
+                        u64::from(other.id())
+                    ),
+                    Some(_) => (),
+                    None => {
+                        colors.insert(other, !color);
+                        stack.push(other);
+                    }
+                }
+            }
+        }
+    }
+
+    trimmed.sort();
+    Ok(trimmed)
+}
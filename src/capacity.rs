@@ -0,0 +1,139 @@
+use handlegraph::packed::{width_for, PagedIntVec};
+use handlegraph::packedgraph::PackedGraph;
+
+use anyhow::Result;
+
+use rayon::prelude::*;
+
+use crate::mmap_gfa::{LineIndices, MmapGFA};
+
+/// The expected size of the graph loaded from a GFA, estimated from
+/// its line index, used to reserve the graph's storage up front
+/// instead of growing it while loading.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GraphCapacity {
+    pub nodes: usize,
+    /// The number of IDs from the smallest to the largest node ID,
+    /// which is the size of the ID to node record map.
+    pub id_span: usize,
+    /// The longest sequence of any node.
+    pub max_sequence_len: usize,
+    pub edges: usize,
+    pub paths: usize,
+    pub steps: usize,
+}
+
+impl GraphCapacity {
+    /// Estimates the capacities from the counts of the index. The
+    /// steps are counted from the separators in the path lines, so
+    /// this reads the P, W, and O lines, but doesn't parse them.
+    /// The ID span assumes dense node IDs, and can be corrected with
+    /// `with_id_range` once the IDs are known.
+    pub fn estimate(
+        mmap_gfa: &MmapGFA,
+        indices: &LineIndices,
+        jumps: bool,
+    ) -> Result<Self> {
+        let nodes = indices.segments.len();
+
+        let max_sequence_len = indices.segment_stats.max_length;
+
+        let (edges, paths) = if indices.gfa2 {
+            (indices.edges.len(), indices.ordered_groups.len())
+        } else if jumps {
+            (
+                indices.links.len() + indices.jumps.len(),
+                indices.paths.len() + indices.walks.len(),
+            )
+        } else {
+            (
+                indices.links.len(),
+                indices.paths.len() + indices.walks.len(),
+            )
+        };
+
+        let count_steps = |offsets: &[usize], separators: &[u8]| {
+            offsets
+                .par_iter()
+                .map(|&offset| {
+                    let line = mmap_gfa.line_at(offset)?;
+                    Ok(line.iter().filter(|b| separators.contains(b)).count())
+                })
+                .sum::<Result<usize>>()
+        };
+
+        // P and O lines have one separator less than steps, W lines
+        // start every step with an orientation
+        let steps = if indices.gfa2 {
+            count_steps(&indices.ordered_groups, b" ")?
+                + indices.ordered_groups.len()
+        } else {
+            count_steps(&indices.paths, b",")?
+                + indices.paths.len()
+                + count_steps(&indices.walks, b"<>")?
+        };
+
+        Ok(GraphCapacity {
+            nodes,
+            id_span: nodes,
+            max_sequence_len,
+            edges,
+            paths,
+            steps,
+        })
+    }
+
+    /// Sets the ID span from the smallest and largest node IDs.
+    pub fn with_id_range(mut self, min_id: usize, max_id: usize) -> Self {
+        if min_id <= max_id {
+            self.id_span = max_id - min_id + 1;
+        }
+        self
+    }
+
+    /// Creates an empty graph with the capacities reserved.
+    ///
+    /// The paged vectors of a `PackedGraph` allocate their pages as
+    /// they're filled, and pre-allocating the pages would break the
+    /// bulk appends used when loading, so only their page tables are
+    /// reserved. The step lists of the paths can't be reserved at
+    /// all, the steps only size the node occurrence records.
+    pub fn new_graph(&self) -> PackedGraph {
+        let mut graph = PackedGraph::default();
+
+        let nodes = &mut graph.nodes;
+        nodes
+            .id_index_map
+            .deque
+            .reserve_with_width(width_for(self.nodes as u64), self.id_span);
+        reserve_pages(&mut nodes.records_vec, 2 * self.nodes);
+        reserve_pages(&mut nodes.node_occurrence_map, self.nodes);
+        nodes.sequences.lengths.reserve_with_width(
+            self.nodes,
+            width_for(self.max_sequence_len as u64),
+        );
+        reserve_pages(&mut nodes.sequences.offsets, self.nodes);
+
+        // every edge has a record with a handle and a next pointer
+        // on each of its node sides
+        reserve_pages(&mut graph.edges.record_vec, 4 * self.edges);
+
+        let occurrences = &mut graph.occurrences;
+        reserve_pages(&mut occurrences.path_ids, self.steps);
+        reserve_pages(&mut occurrences.node_occur_offsets, self.steps);
+        reserve_pages(&mut occurrences.node_occur_next, self.steps);
+
+        let paths = &mut graph.paths;
+        paths.paths.reserve(self.paths);
+        paths.names.name_id_map.reserve(self.paths);
+
+        graph
+    }
+}
+
+/// Reserves the page table of a paged vector for `len` entries.
+fn reserve_pages(vec: &mut PagedIntVec, len: usize) {
+    let pages = len.div_ceil(vec.page_size());
+    vec.pages.reserve(pages.saturating_sub(vec.pages.len()));
+    vec.anchors.reserve(pages);
+}
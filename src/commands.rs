@@ -0,0 +1,172 @@
+use handlegraph::{handlegraph::*, pathhandlegraph::*};
+
+use anyhow::Result;
+
+use structopt::StructOpt;
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::{
+    bluntify::bluntify,
+    io::{
+        packed_graph_from_mmap_with_options, LoadOptions, LoadedGraph,
+        MissingSequences,
+    },
+    load_error::{write_rejected_report, ErrorPolicy},
+    mmap_gfa::MmapGFA,
+};
+
+pub mod consensus;
+pub mod convert;
+pub mod extract;
+pub mod stats;
+pub mod validate;
+
+/// Input options shared by every subcommand that loads a graph.
+#[derive(StructOpt, Debug)]
+pub struct LoadArgs {
+    /// Input GFA, optionally gzip or BGZF compressed. Use `-` to
+    /// read from stdin
+    #[structopt(name = "GFA")]
+    pub gfa: String,
+
+    /// Assign node IDs to segments in file order even if all segment
+    /// names are unsigned integers. Other names are always translated
+    #[structopt(long)]
+    pub translate_names: bool,
+
+    /// Compact numeric segment IDs to the range 1..N, preserving
+    /// their order
+    #[structopt(long)]
+    pub compact_ids: bool,
+
+    /// Keep the optional fields (tags) of segments, links, and paths
+    /// so they can be written back out
+    #[structopt(long)]
+    pub keep_tags: bool,
+
+    /// Don't use or create the `<GFA>.gfai` line index file, which
+    /// otherwise lets repeated runs on the same GFA skip indexing
+    #[structopt(long)]
+    pub no_index: bool,
+
+    /// Skip records that can't be loaded instead of stopping at the
+    /// first one, and report how many were skipped
+    #[structopt(long)]
+    pub lenient: bool,
+
+    /// Write the records skipped by `--lenient` to this TSV file,
+    /// with their line numbers and byte offsets
+    #[structopt(long, requires = "lenient")]
+    pub rejected: Option<String>,
+
+    /// Load J lines (jumps) as edges, and write them back out as J
+    /// lines with their distances. They're ignored otherwise
+    #[structopt(long)]
+    pub jumps: bool,
+
+    /// How to load segments whose sequence is `*`: `literal` loads
+    /// the `*` as a single N, `n` fills them with N's to the
+    /// length in their LN tag, and `length` does the same but writes
+    /// them back out as `*`
+    #[structopt(
+        long,
+        default_value = "literal",
+        possible_values = &["literal", "n", "length"]
+    )]
+    pub missing_sequences: MissingSequences,
+
+    /// Trim the overlapping bases of non-blunt links from one of the
+    /// segments they connect, so that the graph is blunt
+    #[structopt(long)]
+    pub bluntify: bool,
+
+    /// Write the segment name to node ID translation table to this
+    /// TSV file
+    #[structopt(long)]
+    pub translation: Option<String>,
+}
+
+impl LoadArgs {
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            translate_names: self.translate_names,
+            compact_ids: self.compact_ids,
+            keep_tags: self.keep_tags,
+            skip_index_file: self.no_index,
+            policy: if self.lenient {
+                ErrorPolicy::Lenient
+            } else {
+                ErrorPolicy::Strict
+            },
+            jumps: self.jumps,
+            missing_sequences: self.missing_sequences,
+        }
+    }
+}
+
+/// Loads the GFA into a `PackedGraph`, going through `MmapGFA` and
+/// `packed_graph_from_mmap_with_options`. Every subcommand that
+/// needs a graph should load it through here.
+pub fn load_packed_graph(args: &LoadArgs) -> Result<LoadedGraph> {
+    let mut mmap_gfa = MmapGFA::new(&args.gfa)?;
+
+    eprintln!("parsing GFA");
+    let options = args.load_options();
+    let mut loaded =
+        packed_graph_from_mmap_with_options(&mut mmap_gfa, &options)?;
+    eprintln!("PackedGraph constructed");
+
+    if args.bluntify {
+        loaded = bluntify(loaded)?;
+    }
+
+    if let Some(report_path) = args.rejected.as_deref() {
+        let mut out = output_writer(Some(report_path))?;
+        write_rejected_report(&loaded.rejected, &mut out)?;
+        out.flush()?;
+        eprintln!(
+            "wrote {} rejected lines to {}",
+            loaded.rejected.len(),
+            report_path
+        );
+    }
+
+    if let Some(tsv_path) = args.translation.as_deref() {
+        let mut out = output_writer(Some(tsv_path))?;
+        if loaded.id_mapping.write_tsv(&mut out)? {
+            out.flush()?;
+            eprintln!("wrote ID translation table to {}", tsv_path);
+        } else {
+            eprintln!("node IDs were not translated, {} is empty", tsv_path);
+        }
+    }
+
+    Ok(loaded)
+}
+
+/// Opens `out_path` for writing, or stdout if no path was given.
+pub fn output_writer(out_path: Option<&str>) -> Result<Box<dyn Write>> {
+    let writer: Box<dyn Write> = if let Some(path) = out_path {
+        let file = File::create(path)?;
+        Box::new(BufWriter::new(file))
+    } else {
+        Box::new(BufWriter::new(std::io::stdout()))
+    };
+    Ok(writer)
+}
+
+pub fn write_graph_stats<W: Write>(
+    out: &mut W,
+    label: &str,
+    loaded: &LoadedGraph,
+) -> std::io::Result<()> {
+    let graph = &loaded.graph;
+    writeln!(out, "{}", label)?;
+    writeln!(out, "  length: {}", loaded.total_length())?;
+    writeln!(out, "  nodes:  {}", graph.node_count())?;
+    writeln!(out, "  edges:  {}", graph.edge_count())?;
+    writeln!(out, "  paths:  {}", graph.path_count())?;
+    Ok(())
+}
@@ -0,0 +1,222 @@
+use handlegraph::{handlegraph::*, pathhandlegraph::*};
+
+use handlegraph::packedgraph::PackedGraph;
+
+use anyhow::Result;
+
+use structopt::StructOpt;
+
+use std::io::Write;
+
+use crate::{
+    gfa_writer::{write_gfa, Provenance, WriteOptions},
+    io::LoadedGraph,
+    jumps::JumpEdges,
+    node_ids::IdMapping,
+    overlaps::EdgeOverlaps,
+    path_selection::{PathSelection, PathSelectionArgs},
+};
+
+use super::{load_packed_graph, output_writer, write_graph_stats, LoadArgs};
+
+#[derive(StructOpt, Debug)]
+pub struct ConsensusArgs {
+    #[structopt(flatten)]
+    pub load: LoadArgs,
+
+    /// Write the consensus GFA to this file instead of stdout
+    #[structopt(short, long, conflicts_with = "sweep")]
+    pub out: Option<String>,
+
+    /// Maximum jump length used when linking consensus paths
+    #[structopt(long, default_value = "10")]
+    pub jump_max: usize,
+
+    /// Upper bound on the length of the links between consensus
+    /// paths
+    #[structopt(long, default_value = "1000000")]
+    pub jump_limit: usize,
+
+    /// Comma-separated list of jump_max values to build one
+    /// consensus graph for each. The input graph is only loaded once
+    #[structopt(long, require_delimiter = true, requires = "out-prefix")]
+    pub sweep: Vec<usize>,
+
+    /// Prefix of the sweep outputs, required with --sweep. The GFAs
+    /// are written to `<out-prefix>.jump_max_<N>.gfa`, and the
+    /// summary to `<out-prefix>.sweep.tsv`
+    #[structopt(long, requires = "sweep")]
+    pub out_prefix: Option<String>,
+
+    #[structopt(flatten)]
+    pub paths: PathSelectionArgs,
+}
+
+fn build_consensus(
+    graph: &PackedGraph,
+    cons_path_names: &[Vec<u8>],
+    jump_max: usize,
+    jump_limit: usize,
+) -> PackedGraph {
+    eprintln!("starting consensus with jump_max {}", jump_max);
+    handlegraph::consensus::create_consensus_graph(
+        graph,
+        cons_path_names,
+        jump_max,
+        jump_limit,
+    )
+}
+
+/// Writes a consensus graph as GFA, with a provenance header that
+/// records the consensus parameters. The input graph's headers are
+/// carried over, and the consensus nodes of segments without a
+/// sequence are written as `*` like the input's.
+fn write_consensus<W: Write>(
+    input: &LoadedGraph,
+    consensus: PackedGraph,
+    provenance: Provenance,
+    out: &mut W,
+) -> Result<LoadedGraph> {
+    // the consensus graph keeps the node IDs of the input
+    let placeholders = input
+        .placeholders
+        .iter()
+        .copied()
+        .filter(|&id| consensus.has_node(id))
+        .collect();
+    let literal = input
+        .literal
+        .iter()
+        .map(|(&id, &len)| (id, len))
+        .filter(|&(id, _)| consensus.has_node(id))
+        .collect();
+
+    let loaded = LoadedGraph {
+        graph: consensus,
+        id_mapping: IdMapping::Identity,
+        tags: None,
+        headers: input.headers.clone(),
+        rejected: Vec::new(),
+        overlaps: EdgeOverlaps::default(),
+        jumps: JumpEdges::default(),
+        containments: Vec::new(),
+        placeholders,
+        literal,
+    };
+
+    let options = WriteOptions {
+        provenance: Some(provenance),
+        ..WriteOptions::default()
+    };
+
+    write_gfa(&loaded, &options, out)?;
+    out.flush()?;
+
+    Ok(loaded)
+}
+
+fn consensus_provenance(
+    args: &ConsensusArgs,
+    selection: &PathSelection,
+    jump_max: usize,
+) -> Provenance {
+    Provenance::new("consensus")
+        .param("jump_max", jump_max)
+        .param("jump_limit", args.jump_limit)
+        .param("paths", selection)
+}
+
+fn run_sweep(
+    args: &ConsensusArgs,
+    input: &LoadedGraph,
+    selection: &PathSelection,
+    cons_path_names: &[Vec<u8>],
+    out_prefix: &str,
+) -> Result<()> {
+    let graph = &input.graph;
+
+    let tsv_path = format!("{}.sweep.tsv", out_prefix);
+    let mut tsv = output_writer(Some(&tsv_path))?;
+
+    writeln!(tsv, "jump_max\tnodes\tedges\tpaths\tlength\tgfa")?;
+    writeln!(
+        tsv,
+        "input\t{}\t{}\t{}\t{}\t{}",
+        graph.node_count(),
+        graph.edge_count(),
+        graph.path_count(),
+        input.total_length(),
+        args.load.gfa
+    )?;
+
+    for &jump_max in args.sweep.iter() {
+        let consensus =
+            build_consensus(graph, cons_path_names, jump_max, args.jump_limit);
+
+        let gfa_path = format!("{}.jump_max_{}.gfa", out_prefix, jump_max);
+        let mut out = output_writer(Some(&gfa_path))?;
+        let provenance = consensus_provenance(args, selection, jump_max);
+        let consensus =
+            write_consensus(input, consensus, provenance, &mut out)?;
+
+        writeln!(
+            tsv,
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            jump_max,
+            consensus.graph.node_count(),
+            consensus.graph.edge_count(),
+            consensus.graph.path_count(),
+            consensus.total_length(),
+            gfa_path
+        )?;
+        tsv.flush()?;
+
+        eprintln!("wrote {}", gfa_path);
+    }
+
+    eprintln!("wrote sweep summary to {}", tsv_path);
+
+    Ok(())
+}
+
+pub fn run(args: &ConsensusArgs) -> Result<()> {
+    let input = load_packed_graph(&args.load)?;
+    let graph = &input.graph;
+
+    write_graph_stats(&mut std::io::stderr(), "input graph", &input)?;
+    eprintln!();
+
+    let selection = args.paths.selection("Consensus")?;
+
+    eprintln!("getting path names");
+    let cons_path_names = selection.select_path_names(graph)?;
+    eprintln!("selected {} consensus paths", cons_path_names.len());
+
+    if let Some(out_prefix) = args.out_prefix.as_deref() {
+        if !args.sweep.is_empty() {
+            return run_sweep(
+                args,
+                &input,
+                &selection,
+                &cons_path_names,
+                out_prefix,
+            );
+        }
+    }
+
+    let consensus = build_consensus(
+        graph,
+        &cons_path_names,
+        args.jump_max,
+        args.jump_limit,
+    );
+
+    let mut out = output_writer(args.out.as_deref())?;
+    let provenance = consensus_provenance(args, &selection, args.jump_max);
+    let consensus = write_consensus(&input, consensus, provenance, &mut out)?;
+
+    eprintln!();
+    write_graph_stats(&mut std::io::stderr(), "consensus graph", &consensus)?;
+
+    Ok(())
+}
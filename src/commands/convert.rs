@@ -0,0 +1,50 @@
+use anyhow::Result;
+
+use structopt::StructOpt;
+
+use crate::gfa_writer::{write_gfa, Provenance, WriteOptions};
+
+use super::{load_packed_graph, output_writer, LoadArgs};
+
+#[derive(StructOpt, Debug)]
+pub struct ConvertArgs {
+    #[structopt(flatten)]
+    pub load: LoadArgs,
+
+    /// Write the GFA to this file instead of stdout
+    #[structopt(short, long)]
+    pub out: Option<String>,
+
+    /// Write segments with the names or IDs they had in the input,
+    /// rather than the node IDs they were loaded as
+    #[structopt(long)]
+    pub original_ids: bool,
+
+    /// Write paths with PanSN names (sample#haplotype#sequence) as
+    /// GFA 1.1 W lines
+    #[structopt(long)]
+    pub walks: bool,
+}
+
+pub fn run(args: &ConvertArgs) -> Result<()> {
+    let loaded = load_packed_graph(&args.load)?;
+
+    let provenance = Provenance::new("convert")
+        .param("original_ids", args.original_ids)
+        .param("walks", args.walks)
+        .param("keep_tags", args.load.keep_tags)
+        .param("jumps", args.load.jumps)
+        .param("bluntify", args.load.bluntify);
+
+    let options = WriteOptions {
+        original_ids: args.original_ids,
+        walks: args.walks,
+        provenance: Some(provenance),
+    };
+
+    let mut out = output_writer(args.out.as_deref())?;
+    write_gfa(&loaded, &options, &mut out)?;
+    out.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use handlegraph::{handlegraph::*, pathhandlegraph::*};
+
+use handlegraph::packedgraph::PackedGraph;
+
+use anyhow::{bail, Result};
+
+use bstr::ByteSlice;
+
+use structopt::StructOpt;
+
+use std::io::Write;
+
+use super::{load_packed_graph, output_writer, LoadArgs};
+
+#[derive(StructOpt, Debug)]
+pub struct ExtractArgs {
+    #[structopt(flatten)]
+    pub load: LoadArgs,
+
+    /// Write the FASTA to this file instead of stdout
+    #[structopt(short, long)]
+    pub out: Option<String>,
+
+    /// Name of a path to extract, can be given multiple times.
+    /// Extracts every path if omitted
+    #[structopt(short, long = "path", number_of_values = 1)]
+    pub paths: Vec<String>,
+}
+
+fn write_path_fasta<W: Write>(
+    out: &mut W,
+    graph: &PackedGraph,
+    path_id: PathId,
+) -> Result<()> {
+    let name = graph.get_path_name_vec(path_id).unwrap_or_default();
+    writeln!(out, ">{}", name.as_bstr())?;
+
+    let mut seq = Vec::new();
+    if let Some(steps) = graph.path_steps(path_id) {
+        for step in steps {
+            seq.extend(graph.sequence(step.handle()));
+        }
+    }
+
+    for chunk in seq.chunks(80) {
+        out.write_all(chunk)?;
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+pub fn run(args: &ExtractArgs) -> Result<()> {
+    let graph = load_packed_graph(&args.load)?.graph;
+
+    let path_ids = if args.paths.is_empty() {
+        graph.path_ids().collect::<Vec<_>>()
+    } else {
+        let mut path_ids = Vec::with_capacity(args.paths.len());
+        for name in args.paths.iter() {
+            if let Some(path_id) = graph.get_path_id(name.as_bytes()) {
+                path_ids.push(path_id);
+            } else {
+                bail!("Path `{}` not found in {}", name, args.load.gfa);
+            }
+        }
+        path_ids
+    };
+
+    let mut out = output_writer(args.out.as_deref())?;
+    for path_id in path_ids {
+        write_path_fasta(&mut out, &graph, path_id)?;
+    }
+    out.flush()?;
+
+    Ok(())
+}
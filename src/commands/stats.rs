@@ -0,0 +1,21 @@
+use anyhow::Result;
+
+use structopt::StructOpt;
+
+use super::{load_packed_graph, write_graph_stats, LoadArgs};
+
+#[derive(StructOpt, Debug)]
+pub struct StatsArgs {
+    #[structopt(flatten)]
+    pub load: LoadArgs,
+}
+
+pub fn run(args: &StatsArgs) -> Result<()> {
+    let loaded = load_packed_graph(&args.load)?;
+
+    let stdout = std::io::stdout();
+    let mut stdout = stdout.lock();
+    write_graph_stats(&mut stdout, &args.load.gfa, &loaded)?;
+
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use anyhow::{bail, Result};
+
+use structopt::StructOpt;
+
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use crate::{
+    load_error::LoadError,
+    mmap_gfa::MmapGFA,
+    validation::{validate_gfa, Validation},
+};
+
+#[derive(StructOpt, Debug)]
+pub struct ValidateArgs {
+    /// Input GFA, optionally gzip or BGZF compressed. Use `-` to
+    /// read from stdin
+    #[structopt(name = "GFA")]
+    pub gfa: String,
+
+    /// Print the report as JSON
+    #[structopt(long)]
+    pub json: bool,
+
+    /// Don't use or create the `<GFA>.gfai` line index file
+    #[structopt(long)]
+    pub no_index: bool,
+}
+
+pub fn run(args: &ValidateArgs) -> Result<()> {
+    let mmap_gfa = MmapGFA::new(&args.gfa)?;
+
+    eprintln!("indexing GFA");
+    let indices = if args.no_index {
+        mmap_gfa.build_index()?
+    } else {
+        mmap_gfa.load_or_build_index()?
+    };
+
+    eprintln!("validating GFA");
+    let validation = validate_gfa(&mmap_gfa, &indices)?;
+
+    let stdout = std::io::stdout();
+    let mut stdout = stdout.lock();
+    if args.json {
+        write_json(&mut stdout, &args.gfa, &validation)?;
+    } else {
+        write_report(&mut stdout, &args.gfa, &validation)?;
+    }
+    stdout.flush()?;
+
+    if !validation.is_valid() {
+        bail!("{} errors found in {}", validation.errors.len(), args.gfa);
+    }
+
+    Ok(())
+}
+
+/// Number of errors of each kind, by `LoadErrorKind::code`.
+fn error_counts(errors: &[LoadError]) -> BTreeMap<&'static str, usize> {
+    let mut counts = BTreeMap::new();
+    for error in errors {
+        *counts.entry(error.kind.code()).or_default() += 1;
+    }
+    counts
+}
+
+fn write_report<W: Write>(
+    out: &mut W,
+    gfa: &str,
+    validation: &Validation,
+) -> std::io::Result<()> {
+    for error in validation.errors.iter() {
+        writeln!(out, "{}", error)?;
+    }
+
+    let counts = validation.counts;
+    writeln!(
+        out,
+        "checked {} segments, {} links, {} paths",
+        counts.segments, counts.links, counts.paths
+    )?;
+
+    if validation.is_valid() {
+        writeln!(out, "{}: OK", gfa)?;
+    } else {
+        writeln!(out, "{}: {} errors", gfa, validation.errors.len())?;
+        for (code, count) in error_counts(&validation.errors) {
+            writeln!(out, "  {}: {}", code, count)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn json_string(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                res.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+fn json_opt_string(s: Option<&String>) -> String {
+    match s {
+        Some(s) => json_string(s),
+        None => "null".to_string(),
+    }
+}
+
+fn write_json<W: Write>(
+    out: &mut W,
+    gfa: &str,
+    validation: &Validation,
+) -> std::io::Result<()> {
+    let counts = validation.counts;
+
+    writeln!(out, "{{")?;
+    writeln!(out, "  \"file\": {},", json_string(gfa))?;
+    writeln!(out, "  \"valid\": {},", validation.is_valid())?;
+    writeln!(out, "  \"segments\": {},", counts.segments)?;
+    writeln!(out, "  \"links\": {},", counts.links)?;
+    writeln!(out, "  \"paths\": {},", counts.paths)?;
+
+    let error_counts = error_counts(&validation.errors)
+        .into_iter()
+        .map(|(code, count)| format!("{}: {}", json_string(code), count))
+        .collect::<Vec<_>>();
+    writeln!(out, "  \"error_counts\": {{{}}},", error_counts.join(", "))?;
+
+    write!(out, "  \"errors\": [")?;
+    for (ix, error) in validation.errors.iter().enumerate() {
+        let sep = if ix == 0 { "" } else { "," };
+        write!(
+            out,
+            "{}\n    {{\"kind\": {}, \"message\": {}, \"line_type\": \"{}\", \
+             \"line\": {}, \"offset\": {}, \"field\": {}, \"value\": {}, \
+             \"excerpt\": {}}}",
+            sep,
+            json_string(error.kind.code()),
+            json_string(&error.kind.to_string()),
+            error.line_type.line_sig() as char,
+            error.line_number,
+            error.offset,
+            json_opt_string(error.field.as_ref()),
+            json_opt_string(error.value.as_ref()),
+            json_string(&error.excerpt),
+        )?;
+    }
+    if validation.errors.is_empty() {
+        writeln!(out, "]")?;
+    } else {
+        writeln!(out, "\n  ]")?;
+    }
+    writeln!(out, "}}")?;
+
+    Ok(())
+}
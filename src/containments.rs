@@ -0,0 +1,62 @@
+use handlegraph::handle::Handle;
+
+use gfa::gfa::Orientation;
+
+use anyhow::Result;
+
+use crate::fields::{parse_orientation, parse_usize, Fields};
+use crate::overlaps::Cigar;
+
+/// A GFA1 containment (C) line, borrowing the line it was parsed
+/// from. The contained segment is aligned to the container starting
+/// at `pos`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContainmentLine<'a> {
+    pub container: &'a [u8],
+    pub container_orient: Orientation,
+    pub contained: &'a [u8],
+    pub contained_orient: Orientation,
+    pub pos: usize,
+    pub overlap: &'a [u8],
+}
+
+impl<'a> ContainmentLine<'a> {
+    pub fn parse_line(line: &'a [u8]) -> Result<Self> {
+        let mut fields = Fields::new(line, "C")?;
+        let container = fields.next("Container")?;
+        let container_orient = parse_orientation(
+            fields.next("ContainerOrient")?,
+            "ContainerOrient",
+        )?;
+        let contained = fields.next("Contained")?;
+        let contained_orient = parse_orientation(
+            fields.next("ContainedOrient")?,
+            "ContainedOrient",
+        )?;
+        let pos = parse_usize(fields.next("Pos")?, "Pos")?;
+        let overlap = fields.next("Overlap")?;
+
+        Ok(ContainmentLine {
+            container,
+            container_orient,
+            contained,
+            contained_orient,
+            pos,
+            overlap,
+        })
+    }
+}
+
+/// A containment of a loaded graph. A `PackedGraph` has no way to
+/// represent containments, so they're kept alongside it and written
+/// back out as C lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Containment {
+    pub container: Handle,
+    pub contained: Handle,
+    pub pos: usize,
+    /// `None` if the overlap was `*` or blunt.
+    pub overlap: Option<Cigar>,
+    /// The optional fields of the line, if tags were kept.
+    pub tags: Option<Vec<u8>>,
+}
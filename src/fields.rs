@@ -0,0 +1,49 @@
+use gfa::gfa::Orientation;
+
+use anyhow::{anyhow, bail, Result};
+
+use bstr::ByteSlice;
+
+/// Field iterator over a tab-separated line that produces errors
+/// naming the missing field.
+pub struct Fields<'a> {
+    line_type: &'static str,
+    fields: bstr::Split<'a>,
+}
+
+impl<'a> Fields<'a> {
+    /// Starts iterating the fields of `line` after the line type,
+    /// which must be `line_type`.
+    pub fn new(line: &'a [u8], line_type: &'static str) -> Result<Self> {
+        let mut fields = line.trim_end().split_str("\t");
+        if fields.next() != Some(line_type.as_bytes()) {
+            bail!("not a {} line", line_type);
+        }
+        Ok(Self { line_type, fields })
+    }
+
+    pub fn next(&mut self, name: &str) -> Result<&'a [u8]> {
+        self.fields.next().ok_or_else(|| {
+            anyhow!("{} line is missing the {} field", self.line_type, name)
+        })
+    }
+
+    /// Returns the next field if there is one, for optional fields.
+    pub fn next_optional(&mut self) -> Option<&'a [u8]> {
+        self.fields.next()
+    }
+}
+
+pub fn parse_usize(field: &[u8], name: &str) -> Result<usize> {
+    field
+        .to_str()
+        .ok()
+        .and_then(|field| field.parse::<usize>().ok())
+        .ok_or_else(|| anyhow!("invalid {} `{}`", name, field.as_bstr()))
+}
+
+/// Parses a GFA1 orientation field, `+` or `-`.
+pub fn parse_orientation(field: &[u8], name: &str) -> Result<Orientation> {
+    Orientation::from_bytes_plus_minus(field)
+        .ok_or_else(|| anyhow!("invalid {} `{}`", name, field.as_bstr()))
+}
@@ -0,0 +1,220 @@
+use gfa::gfa::Orientation;
+
+use handlegraph::handle::{self, Handle, NodeId};
+
+use anyhow::{bail, Result};
+
+use bstr::ByteSlice;
+
+use crate::fields::{parse_usize, Fields};
+use crate::overlaps::Cigar;
+
+/// Splits a GFA2 reference, e.g. `s1+`, into its name and
+/// orientation.
+fn parse_reference(field: &[u8]) -> Result<(&[u8], Orientation)> {
+    let orient = match field.last() {
+        Some(b'+') => Orientation::Forward,
+        Some(b'-') => Orientation::Backward,
+        _ => bail!("reference `{}` has no orientation", field.as_bstr()),
+    };
+    Ok((&field[..field.len() - 1], orient))
+}
+
+/// A GFA2 segment line, `S sid slen sequence`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment<'a> {
+    pub name: &'a [u8],
+    pub length: usize,
+    pub sequence: &'a [u8],
+}
+
+impl<'a> Segment<'a> {
+    pub fn parse_line(line: &'a [u8]) -> Result<Self> {
+        let mut fields = Fields::new(line, "S")?;
+        let name = fields.next("sid")?;
+        let length = parse_usize(fields.next("slen")?, "slen")?;
+        let sequence = fields.next("sequence")?;
+        Ok(Segment {
+            name,
+            length,
+            sequence,
+        })
+    }
+}
+
+/// A position on a segment. `at_end` is set if the position was
+/// marked with `$`, i.e. it's the end of the segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub pos: usize,
+    pub at_end: bool,
+}
+
+impl Position {
+    fn parse(field: &[u8], name: &str) -> Result<Self> {
+        if let Some(field) = field.strip_suffix(b"$") {
+            let pos = parse_usize(field, name)?;
+            Ok(Position { pos, at_end: true })
+        } else {
+            let pos = parse_usize(field, name)?;
+            Ok(Position { pos, at_end: false })
+        }
+    }
+}
+
+/// Which end of a segment (on its forward strand) an edge interval
+/// covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentSide {
+    Start,
+    End,
+}
+
+/// A GFA2 edge line,
+/// `E eid sid1 sid2 beg1 end1 beg2 end2 alignment`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge<'a> {
+    pub name: &'a [u8],
+    pub sid1: (&'a [u8], Orientation),
+    pub sid2: (&'a [u8], Orientation),
+    pub beg1: Position,
+    pub end1: Position,
+    pub beg2: Position,
+    pub end2: Position,
+    pub alignment: &'a [u8],
+}
+
+fn interval_side(beg: Position, end: Position) -> Option<SegmentSide> {
+    if end.at_end {
+        Some(SegmentSide::End)
+    } else if beg.pos == 0 {
+        Some(SegmentSide::Start)
+    } else {
+        None
+    }
+}
+
+impl<'a> Edge<'a> {
+    pub fn parse_line(line: &'a [u8]) -> Result<Self> {
+        let mut fields = Fields::new(line, "E")?;
+        let name = fields.next("eid")?;
+        let sid1 = parse_reference(fields.next("sid1")?)?;
+        let sid2 = parse_reference(fields.next("sid2")?)?;
+        let beg1 = Position::parse(fields.next("beg1")?, "beg1")?;
+        let end1 = Position::parse(fields.next("end1")?, "end1")?;
+        let beg2 = Position::parse(fields.next("beg2")?, "beg2")?;
+        let end2 = Position::parse(fields.next("end2")?, "end2")?;
+        let alignment = fields.next("alignment")?;
+        Ok(Edge {
+            name,
+            sid1,
+            sid2,
+            beg1,
+            end1,
+            beg2,
+            end2,
+            alignment,
+        })
+    }
+
+    /// True if both intervals are empty.
+    pub fn is_blunt(&self) -> bool {
+        self.beg1.pos == self.end1.pos && self.beg2.pos == self.end2.pos
+    }
+
+    /// The lengths of the intervals on the two segments.
+    pub fn interval_lens(&self) -> (usize, usize) {
+        let len1 = self.end1.pos.saturating_sub(self.beg1.pos);
+        let len2 = self.end2.pos.saturating_sub(self.beg2.pos);
+        (len1, len2)
+    }
+
+    /// The length of the overlap, as the longer of the two
+    /// intervals.
+    pub fn overlap_len(&self) -> usize {
+        let (len1, len2) = self.interval_lens();
+        len1.max(len2)
+    }
+
+    /// The overlap as a CIGAR of matches, if both intervals have the
+    /// same nonzero length.
+    pub fn match_overlap(&self) -> Option<Cigar> {
+        let (len1, len2) = self.interval_lens();
+        if len1 == len2 && len1 > 0 {
+            Cigar::parse(format!("{}M", len1).as_bytes())
+        } else {
+            None
+        }
+    }
+
+    /// If the edge is a dovetail, returns the sides of the two
+    /// segments that it connects. Returns `None` for containments
+    /// and other internal alignments.
+    pub fn dovetail_sides(&self) -> Option<(SegmentSide, SegmentSide)> {
+        let side1 = interval_side(self.beg1, self.end1)?;
+        let side2 = interval_side(self.beg2, self.end2)?;
+
+        // the orientations have to agree with the sides: an edge
+        // between the same sides of two segments flips the strand
+        let same_orient = self.sid1.1 == self.sid2.1;
+        if same_orient == (side1 != side2) {
+            Some((side1, side2))
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the edge between the handles of two segments, with node
+/// IDs `id1` and `id2`, that a dovetail between the given sides
+/// corresponds to.
+pub fn dovetail_edge(
+    sides: (SegmentSide, SegmentSide),
+    id1: NodeId,
+    id2: NodeId,
+) -> handle::Edge {
+    let (left, right) = match sides {
+        (SegmentSide::End, SegmentSide::Start) => {
+            (Handle::pack(id1, false), Handle::pack(id2, false))
+        }
+        (SegmentSide::End, SegmentSide::End) => {
+            (Handle::pack(id1, false), Handle::pack(id2, true))
+        }
+        (SegmentSide::Start, SegmentSide::Start) => {
+            (Handle::pack(id1, true), Handle::pack(id2, false))
+        }
+        (SegmentSide::Start, SegmentSide::End) => {
+            (Handle::pack(id2, false), Handle::pack(id1, false))
+        }
+    };
+    handle::Edge(left, right)
+}
+
+/// A GFA2 group line, either ordered (`O`) or unordered (`U`). The
+/// references are separated by spaces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group<'a> {
+    pub name: &'a [u8],
+    pub references: &'a [u8],
+}
+
+impl<'a> Group<'a> {
+    pub fn parse_ordered(line: &'a [u8]) -> Result<Self> {
+        let mut fields = Fields::new(line, "O")?;
+        let name = fields.next("oid")?;
+        let references = fields.next("references")?;
+        Ok(Group { name, references })
+    }
+
+    /// Iterates through the references of an ordered group. A
+    /// reference without an orientation is returned as an error.
+    pub fn ordered_references(
+        &self,
+    ) -> impl Iterator<
+        Item = std::result::Result<(&'a [u8], Orientation), &'a [u8]>,
+    > + 'a {
+        self.references
+            .split_str(" ")
+            .map(|reference| parse_reference(reference).map_err(|_| reference))
+    }
+}
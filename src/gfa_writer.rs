@@ -0,0 +1,295 @@
+use handlegraph::{
+    handle::{Edge, NodeId},
+    handlegraph::*,
+    pathhandlegraph::*,
+};
+
+use handlegraph::packedgraph::PackedGraph;
+
+use bstr::ByteSlice;
+
+use std::io::Write;
+
+use crate::{io::LoadedGraph, tags::GraphTags, walks::WalkName};
+
+#[derive(Debug, Default, Clone)]
+pub struct WriteOptions {
+    /// Write segments with the names they had in the input GFA,
+    /// rather than their node IDs.
+    pub original_ids: bool,
+    /// Write paths with PanSN names as W lines instead of P lines.
+    pub walks: bool,
+    /// Added as a header line after the input's headers.
+    pub provenance: Option<Provenance>,
+}
+
+/// Records the command and parameters that produced a GFA. It's
+/// written as an H line with the user-defined tags `pg` (program and
+/// version), `cm` (subcommand and parameters), and `cl` (the full
+/// command line), so that the provenance of a file accumulates when
+/// it's passed through several commands.
+#[derive(Debug, Clone)]
+pub struct Provenance {
+    pub command: String,
+    pub params: Vec<(String, String)>,
+}
+
+impl Provenance {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            params: Vec::new(),
+        }
+    }
+
+    pub fn param<T: std::fmt::Display>(mut self, key: &str, value: T) -> Self {
+        self.params.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn write_header<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        // Z values can't contain tabs or line breaks
+        let clean = |s: &str| s.replace(['\t', '\n'], " ");
+
+        write!(
+            out,
+            "H\tpg:Z:{} {}\tcm:Z:{}",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            clean(&self.command)
+        )?;
+        for (key, value) in self.params.iter() {
+            write!(out, " {}={}", clean(key), clean(value))?;
+        }
+
+        let cmd_line = std::env::args().collect::<Vec<_>>().join(" ");
+        writeln!(out, "\tcl:Z:{}", clean(&cmd_line))
+    }
+}
+
+fn fmt_orient(rev: bool) -> &'static str {
+    if rev {
+        "-"
+    } else {
+        "+"
+    }
+}
+
+/// Writes the header and comment lines of the input, after dropping
+/// the version tag from the H lines since the output version is
+/// written separately. H lines with no other tags are left out.
+fn write_input_headers<W: Write>(
+    headers: &[Vec<u8>],
+    out: &mut W,
+) -> std::io::Result<()> {
+    for line in headers {
+        if line.starts_with(b"#") {
+            writeln!(out, "{}", line.as_bstr())?;
+            continue;
+        }
+
+        let fields = line
+            .split_str("\t")
+            .skip(1)
+            .filter(|field| !field.is_empty() && !field.starts_with(b"VN:"))
+            .collect::<Vec<_>>();
+
+        if !fields.is_empty() {
+            writeln!(out, "H\t{}", bstr::join("\t", fields).as_bstr())?;
+        }
+    }
+    Ok(())
+}
+
+fn write_tags<W: Write>(
+    out: &mut W,
+    tags: Option<&[u8]>,
+) -> std::io::Result<()> {
+    if let Some(tags) = tags {
+        write!(out, "\t{}", tags.as_bstr())?;
+    }
+    Ok(())
+}
+
+fn write_walk<W, F>(
+    graph: &PackedGraph,
+    path_id: PathId,
+    walk_name: &WalkName<'_>,
+    write_name: F,
+    tags: Option<&GraphTags>,
+    out: &mut W,
+) -> std::io::Result<()>
+where
+    W: Write,
+    F: Fn(&mut W, NodeId) -> std::io::Result<()>,
+{
+    let (start, end) = if let Some(range) = walk_name.seq_range {
+        range
+    } else {
+        let length = graph
+            .path_steps(path_id)
+            .map(|steps| steps.map(|step| graph.node_len(step.handle())).sum())
+            .unwrap_or(0);
+        (0, length)
+    };
+
+    write!(
+        out,
+        "W\t{}\t{}\t{}\t{}\t{}\t",
+        walk_name.sample.as_bstr(),
+        walk_name.hap_index,
+        walk_name.seq_id.as_bstr(),
+        start,
+        end
+    )?;
+
+    if let Some(steps) = graph.path_steps(path_id) {
+        for step in steps {
+            let handle = step.handle();
+            let orient = if handle.is_reverse() { "<" } else { ">" };
+            write!(out, "{}", orient)?;
+            write_name(out, handle.id())?;
+        }
+    }
+
+    write_tags(out, tags.and_then(|tags| tags.path(path_id)))?;
+    writeln!(out)
+}
+
+/// Writes a loaded graph as GFA. Its ID mapping is used to restore
+/// the input segment names if `options.original_ids` is set, and any
+/// kept tags and link overlaps are written along with their lines.
+/// Edges loaded from jumps are written as J lines, and containments
+/// as C lines after the edges. Nodes with placeholder sequences are
+/// written as `*` with an LN tag.
+/// The input's header and comment lines are all written at the top of
+/// the file, followed by the provenance header, if any.
+pub fn write_gfa<W: Write>(
+    loaded: &LoadedGraph,
+    options: &WriteOptions,
+    out: &mut W,
+) -> std::io::Result<()> {
+    let graph = &loaded.graph;
+    let id_mapping = &loaded.id_mapping;
+    let tags = loaded.tags.as_ref();
+
+    let write_name = |out: &mut W, id: NodeId| -> std::io::Result<()> {
+        if options.original_ids {
+            id_mapping.write_original(out, id)
+        } else {
+            write!(out, "{}", id)
+        }
+    };
+
+    if !loaded.jumps.is_empty() {
+        writeln!(out, "H\tVN:Z:1.2")?;
+    } else if options.walks {
+        writeln!(out, "H\tVN:Z:1.1")?;
+    } else {
+        writeln!(out, "H\tVN:Z:1.0")?;
+    }
+
+    write_input_headers(&loaded.headers, out)?;
+
+    if let Some(provenance) = options.provenance.as_ref() {
+        provenance.write_header(out)?;
+    }
+
+    let mut handles = graph.handles().collect::<Vec<_>>();
+    handles.sort();
+
+    for handle in handles {
+        let segment_tags = tags.and_then(|tags| tags.segment(handle.id()));
+        write!(out, "S\t")?;
+        write_name(out, handle.id())?;
+        // segments without a sequence are written as they were read,
+        // with their length if it's known
+        let length = if loaded.placeholders.contains(&handle.id()) {
+            Some(Some(graph.node_len(handle)))
+        } else {
+            loaded.literal.get(&handle.id()).copied()
+        };
+        if let Some(length) = length {
+            write!(out, "\t*")?;
+            write_tags(out, segment_tags)?;
+            let has_length = segment_tags.is_some_and(|fields| {
+                fields.split_str("\t").any(|f| f.starts_with(b"LN:i:"))
+            });
+            match length {
+                Some(length) if !has_length => {
+                    write!(out, "\tLN:i:{}", length)?
+                }
+                _ => (),
+            }
+        } else {
+            let sequence = graph.sequence_vec(handle.forward());
+            write!(out, "\t{}", sequence.as_bstr())?;
+            write_tags(out, segment_tags)?;
+        }
+        writeln!(out)?;
+    }
+
+    for edge in graph.edges() {
+        let Edge(left, right) = edge;
+        let gap = loaded.jumps.get(edge);
+        write!(out, "{}\t", if gap.is_some() { "J" } else { "L" })?;
+        write_name(out, left.id())?;
+        write!(out, "\t{}\t", fmt_orient(left.is_reverse()))?;
+        write_name(out, right.id())?;
+        write!(out, "\t{}\t", fmt_orient(right.is_reverse()))?;
+        match (gap, loaded.overlaps.get(edge)) {
+            (Some(gap), _) => write!(out, "{}", gap)?,
+            (None, Some(overlap)) => write!(out, "{}", overlap)?,
+            (None, None) => write!(out, "0M")?,
+        }
+        write_tags(out, tags.and_then(|tags| tags.link(edge)))?;
+        writeln!(out)?;
+    }
+
+    for containment in loaded.containments.iter() {
+        let container = containment.container;
+        let contained = containment.contained;
+        write!(out, "C\t")?;
+        write_name(out, container.id())?;
+        write!(out, "\t{}\t", fmt_orient(container.is_reverse()))?;
+        write_name(out, contained.id())?;
+        write!(out, "\t{}\t", fmt_orient(contained.is_reverse()))?;
+        write!(out, "{}\t", containment.pos)?;
+        match containment.overlap.as_ref() {
+            Some(overlap) => write!(out, "{}", overlap)?,
+            None => write!(out, "*")?,
+        }
+        write_tags(out, containment.tags.as_deref())?;
+        writeln!(out)?;
+    }
+
+    for path_id in graph.path_ids() {
+        let path_name = graph.get_path_name_vec(path_id).unwrap_or_default();
+
+        if options.walks {
+            if let Some(walk_name) = WalkName::parse(&path_name) {
+                write_walk(graph, path_id, &walk_name, write_name, tags, out)?;
+                continue;
+            }
+        }
+
+        write!(out, "P\t{}\t", path_name.as_bstr())?;
+
+        if let Some(steps) = graph.path_steps(path_id) {
+            for (ix, step) in steps.enumerate() {
+                if ix != 0 {
+                    write!(out, ",")?;
+                }
+                let handle = step.handle();
+                write_name(out, handle.id())?;
+                write!(out, "{}", fmt_orient(handle.is_reverse()))?;
+            }
+        }
+
+        write!(out, "\t*")?;
+        write_tags(out, tags.and_then(|tags| tags.path(path_id)))?;
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
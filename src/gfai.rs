@@ -0,0 +1,255 @@
+use anyhow::{bail, Result};
+
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+use crate::mmap_gfa::{LineIndices, SegmentStats};
+
+const MAGIC: &[u8; 4] = b"GFAI";
+const VERSION: u32 = 3;
+
+/// Identifies the GFA an index was built from. The index is only
+/// used if the file still has the same size and modification time,
+/// and maps to the same number of bytes (which differ for compressed
+/// files).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Stamp {
+    file_size: u64,
+    mtime_secs: u64,
+    mtime_nanos: u32,
+    data_len: u64,
+}
+
+impl Stamp {
+    fn of_file(gfa_path: &str, data_len: usize) -> Result<Self> {
+        let metadata = std::fs::metadata(gfa_path)?;
+        let mtime = metadata.modified()?.duration_since(UNIX_EPOCH)?;
+        Ok(Self {
+            file_size: metadata.len(),
+            mtime_secs: mtime.as_secs(),
+            mtime_nanos: mtime.subsec_nanos(),
+            data_len: data_len as u64,
+        })
+    }
+}
+
+/// The sidecar index file of a GFA, `<gfa_path>.gfai`.
+pub fn sidecar_path(gfa_path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.gfai", gfa_path))
+}
+
+fn write_offsets(out: &mut Vec<u8>, offsets: &[usize]) {
+    out.extend((offsets.len() as u64).to_le_bytes().iter());
+    for &offset in offsets {
+        out.extend((offset as u64).to_le_bytes().iter());
+    }
+}
+
+fn encode_indices(indices: &LineIndices) -> Vec<u8> {
+    let mut out = Vec::new();
+
+    out.push(indices.gfa2 as u8);
+
+    out.extend((indices.segments.len() as u64).to_le_bytes().iter());
+    for &(offset, length) in indices.segments.iter() {
+        out.extend((offset as u64).to_le_bytes().iter());
+        out.extend((length as u64).to_le_bytes().iter());
+    }
+
+    let stats = &indices.segment_stats;
+    out.push(stats.numeric_names as u8);
+    for value in [
+        stats.min_id,
+        stats.max_id,
+        stats.total_length,
+        stats.max_length,
+    ] {
+        out.extend((value as u64).to_le_bytes().iter());
+    }
+
+    write_offsets(&mut out, &indices.links);
+    write_offsets(&mut out, &indices.jumps);
+    write_offsets(&mut out, &indices.containments);
+    write_offsets(&mut out, &indices.paths);
+    write_offsets(&mut out, &indices.walks);
+    write_offsets(&mut out, &indices.edges);
+    write_offsets(&mut out, &indices.ordered_groups);
+    write_offsets(&mut out, &indices.unordered_groups);
+    write_offsets(&mut out, &indices.headers);
+    write_offsets(&mut out, &indices.other);
+
+    out
+}
+
+/// Reads little-endian integers from a byte slice, failing instead of
+/// panicking on truncated input.
+struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < n {
+            bail!("Index file is truncated");
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    fn offsets(&mut self) -> Result<Vec<usize>> {
+        let len = self.u64()? as usize;
+        if len > self.bytes.len() / 8 {
+            bail!("Index file is truncated");
+        }
+        (0..len).map(|_| Ok(self.u64()? as usize)).collect()
+    }
+}
+
+fn decode_indices(payload: &[u8]) -> Result<LineIndices> {
+    let mut decoder = Decoder { bytes: payload };
+
+    let gfa2 = decoder.u8()? != 0;
+
+    let segment_count = decoder.u64()? as usize;
+    if segment_count > payload.len() / 16 {
+        bail!("Index file is truncated");
+    }
+    let mut segments = Vec::with_capacity(segment_count);
+    for _ in 0..segment_count {
+        let offset = decoder.u64()? as usize;
+        let length = decoder.u64()? as usize;
+        segments.push((offset, length));
+    }
+
+    let segment_stats = SegmentStats {
+        numeric_names: decoder.u8()? != 0,
+        min_id: decoder.u64()? as usize,
+        max_id: decoder.u64()? as usize,
+        total_length: decoder.u64()? as usize,
+        max_length: decoder.u64()? as usize,
+    };
+
+    let links = decoder.offsets()?;
+    let jumps = decoder.offsets()?;
+    let containments = decoder.offsets()?;
+    let paths = decoder.offsets()?;
+    let walks = decoder.offsets()?;
+    let edges = decoder.offsets()?;
+    let ordered_groups = decoder.offsets()?;
+    let unordered_groups = decoder.offsets()?;
+    let headers = decoder.offsets()?;
+    let other = decoder.offsets()?;
+
+    if !decoder.bytes.is_empty() {
+        bail!("Index file has trailing data");
+    }
+
+    Ok(LineIndices {
+        segments,
+        segment_stats,
+        links,
+        jumps,
+        containments,
+        paths,
+        walks,
+        edges,
+        ordered_groups,
+        unordered_groups,
+        headers,
+        other,
+        gfa2,
+    })
+}
+
+/// Writes the index of the GFA at `gfa_path`, which maps to
+/// `data_len` bytes, to its sidecar file. The file is written under a
+/// temporary name first so that a partial index is never read.
+pub fn write_index(
+    gfa_path: &str,
+    data_len: usize,
+    indices: &LineIndices,
+) -> Result<PathBuf> {
+    let stamp = Stamp::of_file(gfa_path, data_len)?;
+    let payload = encode_indices(indices);
+    let checksum = crc32fast::hash(&payload);
+
+    let mut out = Vec::with_capacity(payload.len() + 48);
+    out.extend(MAGIC.iter());
+    out.extend(VERSION.to_le_bytes().iter());
+    out.extend(stamp.file_size.to_le_bytes().iter());
+    out.extend(stamp.mtime_secs.to_le_bytes().iter());
+    out.extend(stamp.mtime_nanos.to_le_bytes().iter());
+    out.extend(stamp.data_len.to_le_bytes().iter());
+    out.extend(checksum.to_le_bytes().iter());
+    out.extend(payload);
+
+    let path = sidecar_path(gfa_path);
+    let tmp_path = PathBuf::from(format!("{}.tmp", path.display()));
+
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(&out)?;
+    file.sync_all()?;
+    std::fs::rename(&tmp_path, &path)?;
+
+    Ok(path)
+}
+
+/// Reads the sidecar index of the GFA at `gfa_path`. Returns `None`
+/// if there is no index, or if it was built from a different version
+/// of the file.
+pub fn read_index(
+    gfa_path: &str,
+    data_len: usize,
+) -> Result<Option<LineIndices>> {
+    let path = sidecar_path(gfa_path);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let bytes = std::fs::read(&path)?;
+    let mut decoder = Decoder { bytes: &bytes };
+
+    if decoder.take(MAGIC.len())? != MAGIC {
+        bail!("{} is not a GFA index", path.display());
+    }
+    if decoder.u32()? != VERSION {
+        return Ok(None);
+    }
+
+    let stamp = Stamp {
+        file_size: decoder.u64()?,
+        mtime_secs: decoder.u64()?,
+        mtime_nanos: decoder.u32()?,
+        data_len: decoder.u64()?,
+    };
+    if stamp != Stamp::of_file(gfa_path, data_len)? {
+        return Ok(None);
+    }
+
+    let checksum = decoder.u32()?;
+    let payload = decoder.bytes;
+    if crc32fast::hash(payload) != checksum {
+        bail!("{} is corrupted, checksum mismatch", path.display());
+    }
+
+    Ok(Some(decode_indices(payload)?))
+}
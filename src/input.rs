@@ -0,0 +1,580 @@
+use anyhow::{bail, Context, Result};
+
+use flate2::read::{GzDecoder, MultiGzDecoder};
+
+use memmap::Mmap;
+
+use bstr::ByteSlice;
+
+use std::fs::File;
+use std::io::{prelude::*, BufReader};
+use std::ops::{Deref, Range};
+use std::sync::{Arc, Mutex};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Length of the fixed part of a gzip member header, up to and
+/// including the XLEN field.
+const GZIP_HEADER_LEN: usize = 12;
+
+/// Length of the CRC32 and ISIZE fields at the end of a gzip member.
+const GZIP_FOOTER_LEN: usize = 8;
+
+/// The compression used by a GFA file, detected from its first bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Bgzf,
+}
+
+impl Compression {
+    pub fn detect(bytes: &[u8]) -> Self {
+        if !bytes.starts_with(&GZIP_MAGIC) {
+            return Compression::None;
+        }
+        if bgzf_block_size(bytes).is_some() {
+            Compression::Bgzf
+        } else {
+            Compression::Gzip
+        }
+    }
+}
+
+/// Returns the total size of the BGZF block at the start of `bytes`,
+/// read from the `BC` extra subfield of its gzip header, or `None` if
+/// it isn't a BGZF block.
+fn bgzf_block_size(bytes: &[u8]) -> Option<usize> {
+    const FEXTRA: u8 = 0x04;
+
+    if bytes.len() < GZIP_HEADER_LEN
+        || !bytes.starts_with(&GZIP_MAGIC)
+        || bytes[3] & FEXTRA == 0
+    {
+        return None;
+    }
+
+    let xlen = u16::from_le_bytes([bytes[10], bytes[11]]) as usize;
+    let extra = bytes.get(GZIP_HEADER_LEN..GZIP_HEADER_LEN + xlen)?;
+
+    let mut ix = 0;
+    while ix + 4 <= extra.len() {
+        let slen = u16::from_le_bytes([extra[ix + 2], extra[ix + 3]]) as usize;
+        if &extra[ix..ix + 2] == b"BC" && slen == 2 {
+            let bsize = extra.get(ix + 4..ix + 6)?;
+            let bsize = u16::from_le_bytes([bsize[0], bsize[1]]) as usize;
+            return Some(bsize + 1);
+        }
+        ix += 4 + slen;
+    }
+
+    None
+}
+
+/// A BGZF block, with its position in the compressed file and the
+/// position its data is decompressed to.
+#[derive(Debug, Clone, Copy)]
+struct BgzfBlock {
+    offset: usize,
+    length: usize,
+    data_offset: usize,
+    data_length: usize,
+}
+
+impl BgzfBlock {
+    fn data_end(&self) -> usize {
+        self.data_offset + self.data_length
+    }
+}
+
+/// Reads the uncompressed size of the BGZF block in `bytes` from its
+/// ISIZE field, checking that the block is complete.
+fn bgzf_data_length(
+    bytes: &[u8],
+    offset: usize,
+    length: usize,
+) -> Result<usize> {
+    let end = offset + length;
+    if end > bytes.len() || length < GZIP_HEADER_LEN + GZIP_FOOTER_LEN {
+        bail!("Truncated BGZF block at offset {}", offset);
+    }
+
+    let isize = &bytes[end - 4..end];
+    Ok(u32::from_le_bytes([isize[0], isize[1], isize[2], isize[3]]) as usize)
+}
+
+/// Walks the block headers of a BGZF file from the block at `offset`,
+/// which is decompressed to `data_offset`, to the end of the file.
+/// The uncompressed size of each block is read from its ISIZE field,
+/// so the blocks can be placed without decompressing anything.
+fn walk_bgzf_blocks(
+    bytes: &[u8],
+    mut offset: usize,
+    mut data_offset: usize,
+    blocks: &mut Vec<BgzfBlock>,
+) -> Result<()> {
+    while offset < bytes.len() {
+        let length = match bgzf_block_size(&bytes[offset..]) {
+            Some(length) => length,
+            None => bail!("Invalid BGZF block at offset {}", offset),
+        };
+        let data_length = bgzf_data_length(bytes, offset, length)?;
+
+        blocks.push(BgzfBlock {
+            offset,
+            length,
+            data_offset,
+            data_length,
+        });
+
+        offset += length;
+        data_offset += data_length;
+    }
+
+    Ok(())
+}
+
+fn bgzf_blocks(bytes: &[u8]) -> Result<Vec<BgzfBlock>> {
+    let mut blocks = Vec::new();
+    walk_bgzf_blocks(bytes, 0, 0, &mut blocks)?;
+    Ok(blocks)
+}
+
+/// Reads the blocks of a BGZF file from its `.gzi` index, as written
+/// by `bgzip -i`. The index holds the compressed and uncompressed
+/// offsets of every block but the first, which starts at 0. The
+/// blocks after the last one in the index, such as the end-of-file
+/// marker, are found by walking their headers.
+fn read_gzi(gzi_path: &str, bytes: &[u8]) -> Result<Vec<BgzfBlock>> {
+    let mut gzi = Vec::new();
+    File::open(gzi_path)?.read_to_end(&mut gzi)?;
+
+    let read_u64 = |ix: usize| -> Result<usize> {
+        match gzi.get(ix * 8..ix * 8 + 8) {
+            Some(value) => {
+                let mut buf = [0; 8];
+                buf.copy_from_slice(value);
+                Ok(u64::from_le_bytes(buf) as usize)
+            }
+            None => bail!("{} is truncated", gzi_path),
+        }
+    };
+
+    let count = read_u64(0)?;
+    if gzi.len() != 8 + count * 16 {
+        bail!("{} has the wrong size for {} blocks", gzi_path, count);
+    }
+
+    let mut starts = vec![(0, 0)];
+    for ix in 0..count {
+        starts.push((read_u64(1 + 2 * ix)?, read_u64(2 + 2 * ix)?));
+    }
+
+    let mut blocks = Vec::with_capacity(starts.len());
+    for pair in starts.windows(2) {
+        let ((offset, data_offset), (next, next_data)) = (pair[0], pair[1]);
+        if next <= offset || next > bytes.len() || next_data < data_offset {
+            bail!("{} has blocks out of order", gzi_path);
+        }
+        // checking the header and footer is cheap, and catches an
+        // index that belongs to another file
+        let length = next - offset;
+        if bgzf_block_size(&bytes[offset..]) != Some(length) {
+            bail!("{} doesn't match the block at offset {}", gzi_path, offset);
+        }
+
+        let data_length = bgzf_data_length(bytes, offset, length)?;
+        if data_offset + data_length != next_data {
+            bail!("{} doesn't match the block at offset {}", gzi_path, offset);
+        }
+
+        blocks.push(BgzfBlock {
+            offset,
+            length,
+            data_offset,
+            data_length,
+        });
+    }
+
+    let (offset, data_offset) = starts[starts.len() - 1];
+    walk_bgzf_blocks(bytes, offset, data_offset, &mut blocks)?;
+
+    Ok(blocks)
+}
+
+/// The number of shards of the block cache. Consecutive blocks go
+/// to different shards, so threads reading nearby lines rarely wait
+/// for each other.
+const BLOCK_CACHE_SHARDS: usize = 16;
+
+/// The number of decompressed blocks each shard of the cache keeps,
+/// for a total of about 16 MB with the usual 64 KB blocks.
+const BLOCK_CACHE_SHARD_LEN: usize = 16;
+
+#[derive(Default)]
+struct CacheShard {
+    /// Block index, data, and the tick it was last used at.
+    blocks: Vec<(usize, Arc<[u8]>, u64)>,
+    tick: u64,
+}
+
+/// A bounded cache of decompressed BGZF blocks, which evicts the
+/// least recently used block of a shard when the shard is full.
+struct BlockCache {
+    shards: Vec<Mutex<CacheShard>>,
+}
+
+impl BlockCache {
+    fn new() -> Self {
+        let shards = (0..BLOCK_CACHE_SHARDS)
+            .map(|_| Mutex::new(CacheShard::default()))
+            .collect();
+        Self { shards }
+    }
+
+    fn get(&self, ix: usize) -> Option<Arc<[u8]>> {
+        let mut shard = self.shards[ix % BLOCK_CACHE_SHARDS].lock().unwrap();
+        shard.tick += 1;
+        let tick = shard.tick;
+        let (_, data, used) =
+            shard.blocks.iter_mut().find(|(block, _, _)| *block == ix)?;
+        *used = tick;
+        Some(data.clone())
+    }
+
+    fn insert(&self, ix: usize, data: Arc<[u8]>) {
+        let mut shard = self.shards[ix % BLOCK_CACHE_SHARDS].lock().unwrap();
+        shard.tick += 1;
+        let tick = shard.tick;
+        // another thread may have decompressed the same block
+        if shard.blocks.iter().any(|(block, _, _)| *block == ix) {
+            return;
+        }
+        if shard.blocks.len() < BLOCK_CACHE_SHARD_LEN {
+            shard.blocks.push((ix, data, tick));
+        } else if let Some(oldest) =
+            shard.blocks.iter_mut().min_by_key(|(_, _, used)| *used)
+        {
+            *oldest = (ix, data, tick);
+        }
+    }
+}
+
+/// A BGZF file that's read by block. Blocks are decompressed when a
+/// line in them is read, and only the most recently used ones are
+/// kept, so the line offsets of the index are positions in the
+/// uncompressed data, but the uncompressed data is never all in
+/// memory at once.
+pub struct BgzfReader {
+    bytes: Mmap,
+    blocks: Vec<BgzfBlock>,
+    cache: BlockCache,
+    len: usize,
+}
+
+impl BgzfReader {
+    /// Uses the blocks from the `.gzi` index of the file if there's
+    /// a valid one, otherwise finds them by walking the block headers.
+    fn new(bytes: Mmap, path: &str) -> Result<Self> {
+        let gzi_path = format!("{}.gzi", path);
+        let blocks = if std::path::Path::new(&gzi_path).exists() {
+            match read_gzi(&gzi_path, &bytes) {
+                Ok(blocks) => {
+                    eprintln!("loaded BGZF block index from {}", gzi_path);
+                    blocks
+                }
+                Err(err) => {
+                    eprintln!("ignoring BGZF block index: {}", err);
+                    bgzf_blocks(&bytes)?
+                }
+            }
+        } else {
+            bgzf_blocks(&bytes)?
+        };
+
+        let len = blocks.last().map(BgzfBlock::data_end).unwrap_or(0);
+        if len == 0 {
+            bail!("The BGZF input is empty");
+        }
+        eprintln!("{} BGZF blocks ({} bytes uncompressed)", blocks.len(), len);
+
+        Ok(Self {
+            bytes,
+            blocks,
+            cache: BlockCache::new(),
+            len,
+        })
+    }
+
+    /// The index of the block holding the byte at `offset`, skipping
+    /// empty blocks such as the end-of-file marker.
+    fn block_index(&self, offset: usize) -> usize {
+        self.blocks
+            .partition_point(|block| block.data_end() <= offset)
+    }
+
+    /// Returns the decompressed data of a block, from the cache if
+    /// it's there. A block that fails to decompress is an error, as
+    /// a `.gfai` index may have been saved for an intact file.
+    fn block(&self, ix: usize) -> Result<Arc<[u8]>> {
+        if let Some(data) = self.cache.get(ix) {
+            return Ok(data);
+        }
+
+        let block = &self.blocks[ix];
+        let input = &self.bytes[block.offset..block.offset + block.length];
+        let mut data = vec![0; block.data_length];
+        GzDecoder::new(input)
+            .read_exact(&mut data)
+            .with_context(|| {
+                format!(
+                    "Failed to decompress BGZF block at offset {}",
+                    block.offset
+                )
+            })?;
+
+        let data: Arc<[u8]> = data.into();
+        self.cache.insert(ix, data.clone());
+        Ok(data)
+    }
+
+    /// Returns the position of the first newline at or after
+    /// `offset`.
+    fn find_newline(&self, offset: usize) -> Result<Option<usize>> {
+        let first = self.block_index(offset);
+        for (ix, block) in self.blocks.iter().enumerate().skip(first) {
+            let start = offset.saturating_sub(block.data_offset);
+            if let Some(pos) = self.block(ix)?[start..].find_byte(b'\n') {
+                return Ok(Some(block.data_offset + start + pos));
+            }
+        }
+        Ok(None)
+    }
+
+    fn line(&self, offset: usize) -> Result<LineRef<'_>> {
+        if offset >= self.len {
+            return Ok(LineRef::Borrowed(&[]));
+        }
+
+        let ix = self.block_index(offset);
+        let block = &self.blocks[ix];
+        let data = self.block(ix)?;
+        let start = offset - block.data_offset;
+        if let Some(pos) = data[start..].find_byte(b'\n') {
+            return Ok(LineRef::Shared(data, start..start + pos + 1));
+        }
+
+        // the last line that starts in a block can continue into the
+        // next ones, and is joined into a copy
+        let end = self
+            .find_newline(block.data_end())?
+            .map(|pos| pos + 1)
+            .unwrap_or(self.len);
+
+        let mut line = Vec::with_capacity(end - offset);
+        for (ix, block) in self.blocks.iter().enumerate().skip(ix) {
+            if block.data_offset >= end {
+                break;
+            }
+            let start = offset.saturating_sub(block.data_offset);
+            let stop = (end - block.data_offset).min(block.data_length);
+            line.extend_from_slice(&self.block(ix)?[start..stop]);
+        }
+        let len = line.len();
+        Ok(LineRef::Shared(line.into(), 0..len))
+    }
+
+    fn count_lines(&self, start: usize, end: usize) -> Result<usize> {
+        let first = self.block_index(start);
+        let mut count = 0;
+        for (ix, block) in self.blocks.iter().enumerate().skip(first) {
+            if block.data_offset >= end {
+                break;
+            }
+            let from = start.saturating_sub(block.data_offset);
+            let to = (end - block.data_offset).min(block.data_length);
+            count += count_newlines(&self.block(ix)?[from..to]);
+        }
+        Ok(count)
+    }
+}
+
+/// A line of the uncompressed data, either borrowed from the mapped
+/// file or sharing the decompressed BGZF block it's in, which keeps
+/// the block alive after it's evicted from the cache.
+#[derive(Debug, Clone)]
+pub enum LineRef<'a> {
+    Borrowed(&'a [u8]),
+    Shared(Arc<[u8]>, Range<usize>),
+}
+
+impl<'a> LineRef<'a> {
+    /// Shortens the line to its first `len` bytes.
+    pub fn truncate(&mut self, len: usize) {
+        match self {
+            LineRef::Borrowed(line) => *line = &line[..len.min(line.len())],
+            LineRef::Shared(_, range) => {
+                range.end = range.end.min(range.start + len)
+            }
+        }
+    }
+}
+
+impl<'a> Deref for LineRef<'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            LineRef::Borrowed(line) => line,
+            LineRef::Shared(data, range) => &data[range.clone()],
+        }
+    }
+}
+
+impl<'a> AsRef<[u8]> for LineRef<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+fn count_newlines(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&b| b == b'\n').count()
+}
+
+/// The uncompressed bytes of a GFA, either mapped into memory or read
+/// by block from a BGZF file. Lines are addressed by their offset in
+/// the uncompressed data either way.
+pub enum GfaData {
+    Mapped(Mmap),
+    Bgzf(BgzfReader),
+}
+
+impl GfaData {
+    pub fn len(&self) -> usize {
+        match self {
+            GfaData::Mapped(mmap) => mmap.len(),
+            GfaData::Bgzf(reader) => reader.len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the line starting at `offset` including its newline,
+    /// or an empty line at the end of the data.
+    #[inline]
+    pub fn line(&self, offset: usize) -> Result<LineRef<'_>> {
+        match self {
+            GfaData::Mapped(mmap) => {
+                let bytes = &mmap[offset.min(mmap.len())..];
+                let line = match bytes.find_byte(b'\n') {
+                    Some(ix) => &bytes[..=ix],
+                    None => bytes,
+                };
+                Ok(LineRef::Borrowed(line))
+            }
+            GfaData::Bgzf(reader) => reader.line(offset),
+        }
+    }
+
+    /// Returns the offset of the first line that starts at or after
+    /// `offset`.
+    pub fn next_line_start(&self, offset: usize) -> Result<usize> {
+        if offset == 0 || offset >= self.len() {
+            return Ok(offset.min(self.len()));
+        }
+
+        let newline = match self {
+            GfaData::Mapped(mmap) => mmap[offset - 1..]
+                .find_byte(b'\n')
+                .map(|ix| offset - 1 + ix),
+            GfaData::Bgzf(reader) => reader.find_newline(offset - 1)?,
+        };
+        Ok(newline.map(|ix| ix + 1).unwrap_or_else(|| self.len()))
+    }
+
+    /// Counts the newlines in `start..end`.
+    pub fn count_lines(&self, start: usize, end: usize) -> Result<usize> {
+        match self {
+            GfaData::Mapped(mmap) => Ok(count_newlines(&mmap[start..end])),
+            GfaData::Bgzf(reader) => reader.count_lines(start, end),
+        }
+    }
+}
+
+/// Decompresses a gzip stream into a temporary spool file and maps
+/// that.
+fn spool_gzip<R: Read>(input: R) -> Result<Mmap> {
+    let mut decoder = MultiGzDecoder::new(input);
+    let mut spool = tempfile::tempfile()?;
+
+    let written = std::io::copy(&mut decoder, &mut spool)
+        .context("Failed to decompress gzip input")?;
+    eprintln!("decompressed {} bytes of gzip input", written);
+
+    if written == 0 {
+        bail!("The gzip input is empty");
+    }
+
+    Ok(unsafe { Mmap::map(&spool)? })
+}
+
+/// Copies a stream that can't be mapped, such as stdin or a pipe, to
+/// a temporary spool file and maps that. Gzip streams, including
+/// BGZF, are decompressed on the way.
+fn spool_stream<R: BufRead>(mut input: R, name: &str) -> Result<Mmap> {
+    let is_gzip = input.fill_buf()?.starts_with(&GZIP_MAGIC);
+    if is_gzip {
+        eprintln!("{} is gzip compressed", name);
+        return spool_gzip(input);
+    }
+
+    let mut spool = tempfile::tempfile()?;
+    let written = std::io::copy(&mut input, &mut spool)
+        .with_context(|| format!("Failed to read {}", name))?;
+    eprintln!("spooled {} bytes from {}", written, name);
+
+    if written == 0 {
+        bail!("{} is empty", name);
+    }
+
+    Ok(unsafe { Mmap::map(&spool)? })
+}
+
+/// Maps a GFA file into memory. BGZF files are detected by their
+/// header and read by block, other gzip files are decompressed to a
+/// temporary spool file first. The path `-` reads the GFA from
+/// stdin, and other inputs that can't be mapped, like pipes and
+/// process substitutions, are spooled the same way.
+pub fn map_gfa(path: &str) -> Result<GfaData> {
+    if path == "-" {
+        let stdin = std::io::stdin();
+        return Ok(GfaData::Mapped(spool_stream(stdin.lock(), "stdin")?));
+    }
+
+    let file =
+        File::open(path).with_context(|| format!("Failed to open {}", path))?;
+
+    if !file.metadata()?.is_file() {
+        let spool = spool_stream(BufReader::new(file), path)?;
+        return Ok(GfaData::Mapped(spool));
+    }
+
+    if file.metadata()?.len() == 0 {
+        bail!("{} is empty", path);
+    }
+    let mmap = unsafe { Mmap::map(&file)? };
+
+    match Compression::detect(&mmap) {
+        Compression::None => Ok(GfaData::Mapped(mmap)),
+        Compression::Bgzf => {
+            eprintln!("{} is BGZF compressed", path);
+            Ok(GfaData::Bgzf(BgzfReader::new(mmap, path)?))
+        }
+        Compression::Gzip => {
+            eprintln!("{} is gzip compressed", path);
+            Ok(GfaData::Mapped(spool_gzip(&mmap[..])?))
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use handlegraph::{
-    handle::{Edge, Handle},
+    handle::{Edge, Handle, NodeId},
+    handlegraph::*,
     mutablehandlegraph::*,
     pathhandlegraph::*,
 };
@@ -8,16 +9,37 @@ use handlegraph::packedgraph::PackedGraph;
 
 use succinct::SpaceUsage;
 
-use gfa::gfa::Line;
+use gfa::{gfa::Line, parser::GFAParser};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 
-use fxhash::FxHashMap;
+use bstr::ByteSlice;
+
+use fxhash::{FxHashMap, FxHashSet};
+
+use rayon::prelude::*;
+
+use std::borrow::Cow;
+use std::sync::Mutex;
+use std::time::Instant;
 
 #[allow(unused_imports)]
 use crate::{
+    capacity::GraphCapacity,
+    containments::{Containment, ContainmentLine},
+    gfa2,
     interface::{LoadGFAMsg, LoadGFAView},
-    mmap_gfa::{LineIndices, LineType, MmapGFA},
+    jumps::{Jump, JumpEdges},
+    load_error::{
+        rejected_counts, ErrorPolicy, LoadError, LoadErrorKind, RejectedLines,
+    },
+    mmap_gfa::{self, LineIndices, LineType, MmapGFA, SegmentStats},
+    node_ids::{IdMapping, NodeIdMap},
+    overlaps::{self, EdgeOverlaps},
+    path_line::PathLine,
+    segment_names::SegmentNameMap,
+    tags::{self, GraphTags},
+    walks::Walk,
 };
 
 #[allow(unused_imports)]
@@ -31,104 +53,739 @@ use tokio::{
     time::sleep,
 };
 
-pub fn packed_graph_from_mmap(mmap_gfa: &mut MmapGFA) -> Result<PackedGraph> {
-    let indices = mmap_gfa.build_index()?;
+/// How segments without a sequence (`*`) are loaded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MissingSequences {
+    /// Use the `*` as the sequence, which is stored as a single N.
+    #[default]
+    Literal,
+    /// Fill the node with as many N's as the segment's length, given
+    /// by the LN tag in GFA1. The N's become the node's sequence.
+    Ns,
+    /// Fill the node with N's like `Ns`, but write it back out as a
+    /// `*` with its length, so only the length is kept.
+    LengthOnly,
+}
 
-    // let mut graph =
-    //     PackedGraph::with_expected_node_count(indices.segments.len());
+impl std::str::FromStr for MissingSequences {
+    type Err = anyhow::Error;
 
-    let mut graph = PackedGraph::default();
-    eprintln!("empty space usage: {} bytes", graph.total_bytes());
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "literal" => Ok(MissingSequences::Literal),
+            "n" => Ok(MissingSequences::Ns),
+            "length" => Ok(MissingSequences::LengthOnly),
+            _ => bail!("unknown missing sequence mode `{}`", s),
+        }
+    }
+}
 
-    let mut min_id = std::usize::MAX;
-    let mut max_id = 0;
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LoadOptions {
+    /// Assign node IDs to segments in file order even if every
+    /// segment name is an unsigned integer.
+    pub translate_names: bool,
+    /// Compact numeric segment names to the node IDs `1..=N`,
+    /// preserving their order.
+    pub compact_ids: bool,
+    /// Keep the optional fields of segments, links, and paths.
+    pub keep_tags: bool,
+    /// Always build the line index, without reading or writing the
+    /// `.gfai` sidecar file.
+    pub skip_index_file: bool,
+    /// Whether a bad record aborts the load, or is skipped.
+    pub policy: ErrorPolicy,
+    /// Load GFA1 jumps (J lines) as edges. They're ignored otherwise.
+    pub jumps: bool,
+    /// How to load segments without a sequence.
+    pub missing_sequences: MissingSequences,
+}
 
-    for &offset in indices.segments.iter() {
-        let _line = mmap_gfa.read_line_at(offset.0)?;
-        let name = mmap_gfa.current_line_name().unwrap();
-        let name_str = std::str::from_utf8(name).unwrap();
-        let id = name_str.parse::<usize>().unwrap();
+/// A graph loaded from a GFA, together with the mapping from the
+/// GFA's segment names to the graph's node IDs, the header and
+/// comment lines, and the optional fields if they were kept.
+pub struct LoadedGraph {
+    pub graph: PackedGraph,
+    pub id_mapping: IdMapping,
+    pub tags: Option<GraphTags>,
+    /// The H and `#` lines of the input, in file order and without
+    /// line endings.
+    pub headers: Vec<Vec<u8>>,
+    /// The records that were skipped by a lenient load, in file
+    /// order.
+    pub rejected: Vec<LoadError>,
+    /// The overlaps of the edges that aren't blunt.
+    pub overlaps: EdgeOverlaps,
+    /// The edges that were loaded from jumps, with their gaps.
+    pub jumps: JumpEdges,
+    /// The containments (C lines) of the input, in file order.
+    pub containments: Vec<Containment>,
+    /// The nodes of segments without a sequence that were filled
+    /// with N's, with `MissingSequences::LengthOnly`.
+    pub placeholders: FxHashSet<NodeId>,
+    /// The nodes of segments without a sequence that were loaded as
+    /// a single N, with `MissingSequences::Literal`, and the lengths
+    /// of those segments if they're known.
+    pub literal: FxHashMap<NodeId, Option<usize>>,
+}
 
-        min_id = id.min(min_id);
-        max_id = id.max(max_id);
+impl LoadedGraph {
+    /// The total length of the node sequences, where a node loaded
+    /// from a literal `*` counts as the length of its segment if
+    /// that's known, rather than as the single N it holds.
+    pub fn total_length(&self) -> usize {
+        let literal_length: usize =
+            self.literal.values().map(|len| len.unwrap_or(1)).sum();
+        self.graph.total_length() - self.literal.len() + literal_length
     }
+}
 
-    let id_offset = if min_id == 0 { 1 } else { 0 };
+pub fn packed_graph_from_mmap(mmap_gfa: &mut MmapGFA) -> Result<PackedGraph> {
+    let options = LoadOptions::default();
+    let loaded = packed_graph_from_mmap_with_options(mmap_gfa, &options)?;
+    Ok(loaded.graph)
+}
 
-    eprintln!("adding nodes");
-    for &offset in indices.segments.iter() {
-        let _line = mmap_gfa.read_line_at(offset.0)?;
-        let segment = mmap_gfa.parse_current_line()?;
+/// Loads the GFA into a `PackedGraph`. If any of the segment names
+/// isn't an unsigned integer, or `options.translate_names` is set,
+/// the segments are assigned dense node IDs in file order. Numeric
+/// names are used as node IDs directly, shifted by one if they're
+/// 0-based, or compacted if `options.compact_ids` is set.
+///
+/// Records that can't be loaded are handled according to
+/// `options.policy`: a strict load fails with the `LoadError` of the
+/// first one, a lenient load skips them and returns them in
+/// `LoadedGraph::rejected`.
+pub fn packed_graph_from_mmap_with_options(
+    mmap_gfa: &mut MmapGFA,
+    options: &LoadOptions,
+) -> Result<LoadedGraph> {
+    let mut timer = PhaseTimer::start();
 
-        if let gfa::gfa::Line::Segment(segment) = segment {
-            let id = (segment.name + id_offset) as u64;
-            graph.create_handle(&segment.sequence, id);
-        }
+    let indices = if options.skip_index_file {
+        mmap_gfa.build_index()?
+    } else {
+        mmap_gfa.load_or_build_index()?
+    };
+    eprintln!(
+        "index of {} segments ({} bp) ready in {:.2}s",
+        indices.segments.len(),
+        indices.segment_stats.total_length,
+        timer.lap()
+    );
+
+    let mmap_gfa: &MmapGFA = mmap_gfa;
+
+    let headers = read_header_lines(mmap_gfa, &indices)?;
+
+    let rejects = RejectedLines::new(options.policy, mmap_gfa.data());
+
+    let mut side = SideTables {
+        tags: options.keep_tags.then(GraphTags::default),
+        overlaps: EdgeOverlaps::default(),
+        sequences: SegmentSequences::new(options.missing_sequences),
+    };
+
+    let SegmentStats {
+        numeric_names,
+        min_id,
+        max_id,
+        ..
+    } = indices.segment_stats;
+    let numeric_names = numeric_names && !options.translate_names;
+
+    let id_mapping = if !numeric_names {
+        eprintln!("translating segment names to node IDs");
+        let names = SegmentNameMap::with_capacity(indices.segments.len());
+        IdMapping::Names(names)
+    } else if options.compact_ids {
+        eprintln!("compacting node IDs");
+        let all_ids = indices
+            .segments
+            .par_iter()
+            .map(|&(offset, _)| {
+                let line = mmap_gfa.line_at(offset)?;
+                let id = mmap_gfa::line_name(&line)
+                    .and_then(|name| name.to_str().ok()?.parse::<u64>().ok());
+                Ok(id)
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        IdMapping::Compact(NodeIdMap::from_ids(all_ids))
+    } else if min_id == 0 {
+        IdMapping::Offset(1)
+    } else {
+        IdMapping::Identity
+    };
+
+    if numeric_names
+        && !options.compact_ids
+        && max_id > 2 * indices.segments.len()
+    {
+        eprintln!(
+            "segment IDs are sparse (max ID {} for {} segments), \
+             consider compacting them",
+            max_id,
+            indices.segments.len()
+        );
     }
+
+    let capacity = GraphCapacity::estimate(mmap_gfa, &indices, options.jumps)?;
+    let capacity = match id_mapping {
+        IdMapping::Offset(_) | IdMapping::Identity => {
+            capacity.with_id_range(min_id, max_id)
+        }
+        _ => capacity,
+    };
+
+    eprintln!(
+        "reserving space for {} nodes, {} edges, and {} path steps",
+        capacity.nodes, capacity.edges, capacity.steps
+    );
+    let mut graph = capacity.new_graph();
     eprintln!(
-        "after segments - space usage: {} bytes",
-        graph.total_bytes()
+        "empty space usage: {} bytes, {:.2}s",
+        graph.total_bytes(),
+        timer.lap()
     );
 
-    eprintln!("adding edges");
+    let id_mapping = if indices.gfa2 {
+        eprintln!("loading GFA2");
+        add_gfa2_lines(
+            &mut graph, mmap_gfa, &indices, id_mapping, &mut side, &rejects,
+        )?
+    } else {
+        add_gfa1_lines(
+            &mut graph, mmap_gfa, &indices, id_mapping, &mut side, &rejects,
+        )?
+    };
 
-    let edges_iter = indices.links.iter().filter_map(|&offset| {
-        let _line = mmap_gfa.read_line_at(offset).ok()?;
-        let link = mmap_gfa.parse_current_line().ok()?;
+    let SideTables {
+        mut tags,
+        overlaps,
+        sequences,
+    } = side;
+
+    let mut jumps = JumpEdges::default();
+    let mut containments = Vec::new();
+    if !indices.gfa2 {
+        if options.jumps {
+            add_jumps(
+                &mut graph,
+                mmap_gfa,
+                &indices,
+                &id_mapping,
+                &mut tags,
+                &mut jumps,
+                &rejects,
+            )?;
+        }
+        containments = add_containments(
+            &graph,
+            mmap_gfa,
+            &indices,
+            &id_mapping,
+            tags.is_some(),
+            &sequences,
+            &rejects,
+        )?;
+    }
 
-        if let gfa::gfa::Line::Link(link) = link {
-            let from_id = (link.from_segment + id_offset) as u64;
-            let to_id = (link.to_segment + id_offset) as u64;
+    let (placeholders, literal) = sequences.finish();
+
+    report_unused_lines(mmap_gfa, &indices, options)?;
 
-            let from = Handle::new(from_id, link.from_orient);
-            let to = Handle::new(to_id, link.to_orient);
+    let rejected = rejects.finish()?;
+    if !rejected.is_empty() {
+        let counts = rejected_counts(&rejected)
+            .into_iter()
+            .map(|(line_type, count)| {
+                format!("{} {}", count, line_type.line_sig() as char)
+            })
+            .collect::<Vec<_>>();
+        eprintln!(
+            "skipped {} bad lines ({})",
+            rejected.len(),
+            counts.join(", ")
+        );
+    }
+
+    Ok(LoadedGraph {
+        graph,
+        id_mapping,
+        tags,
+        headers,
+        rejected,
+        overlaps,
+        jumps,
+        containments,
+        placeholders,
+        literal,
+    })
+}
+
+/// Adds the J lines of a GFA1 as edges, and records their gaps in
+/// `jumps`. A jump between segment ends that are also linked is
+/// dropped in favor of the link.
+fn add_jumps(
+    graph: &mut PackedGraph,
+    mmap_gfa: &MmapGFA,
+    indices: &LineIndices,
+    id_mapping: &IdMapping,
+    tags: &mut Option<GraphTags>,
+    jumps: &mut JumpEdges,
+    rejects: &RejectedLines<'_>,
+) -> Result<()> {
+    let mut edges = Vec::with_capacity(indices.jumps.len());
+
+    for &offset in indices.jumps.iter() {
+        let line = mmap_gfa.line_at(offset)?;
+        let line = &*line;
+        let jump = match Jump::parse_line(line) {
+            Ok(jump) => jump,
+            Err(err) => {
+                rejects.reject(parse_error(
+                    err,
+                    LineType::Jump,
+                    line,
+                    offset,
+                ))?;
+                continue;
+            }
+        };
+
+        let node_id = |name: &[u8]| {
+            id_mapping.segment_id(name).filter(|&id| graph.has_node(id))
+        };
+
+        let from_id = node_id(jump.from_segment);
+        let to_id = node_id(jump.to_segment);
+        let (from_id, to_id) = match (from_id, to_id) {
+            (Some(from_id), Some(to_id)) => (from_id, to_id),
+            (None, _) => {
+                rejects.reject(missing_segment_error(
+                    LineType::Jump,
+                    line,
+                    offset,
+                    "From",
+                    jump.from_segment,
+                ))?;
+                continue;
+            }
+            (_, None) => {
+                rejects.reject(missing_segment_error(
+                    LineType::Jump,
+                    line,
+                    offset,
+                    "To",
+                    jump.to_segment,
+                ))?;
+                continue;
+            }
+        };
+
+        let from = Handle::new(from_id, jump.from_orient);
+        let to = Handle::new(to_id, jump.to_orient);
+        if graph.has_edge(from, to) {
+            continue;
+        }
+
+        let edge = Edge(from, to);
+        jumps.insert(edge, jump.distance);
+        if let Some(tags) = tags.as_mut() {
+            tags.add_link(edge, line, tags::JUMP_FIELDS);
+        }
+        edges.push(edge);
+    }
 
-            Some(Edge(from, to))
+    graph.create_edges_iter(edges.into_iter());
+
+    eprintln!("added {} jumps as edges", jumps.len());
+
+    Ok(())
+}
+
+/// Reads the C lines of a GFA1, checking that the contained segment
+/// fits in the container.
+fn add_containments(
+    graph: &PackedGraph,
+    mmap_gfa: &MmapGFA,
+    indices: &LineIndices,
+    id_mapping: &IdMapping,
+    keep_tags: bool,
+    sequences: &SegmentSequences,
+    rejects: &RejectedLines<'_>,
+) -> Result<Vec<Containment>> {
+    let mut containments = Vec::with_capacity(indices.containments.len());
+
+    for &offset in indices.containments.iter() {
+        let line = mmap_gfa.line_at(offset)?;
+        let line = &*line;
+        let lt = LineType::Containment;
+        let containment = match ContainmentLine::parse_line(line) {
+            Ok(containment) => containment,
+            Err(err) => {
+                rejects.reject(parse_error(err, lt, line, offset))?;
+                continue;
+            }
+        };
+
+        let node_id = |name: &[u8]| {
+            id_mapping.segment_id(name).filter(|&id| graph.has_node(id))
+        };
+
+        let container_id = node_id(containment.container);
+        let contained_id = node_id(containment.contained);
+        let (container_id, contained_id) = match (container_id, contained_id) {
+            (Some(container_id), Some(contained_id)) => {
+                (container_id, contained_id)
+            }
+            (None, _) => {
+                rejects.reject(missing_segment_error(
+                    lt,
+                    line,
+                    offset,
+                    "Container",
+                    containment.container,
+                ))?;
+                continue;
+            }
+            (_, None) => {
+                rejects.reject(missing_segment_error(
+                    lt,
+                    line,
+                    offset,
+                    "Contained",
+                    containment.contained,
+                ))?;
+                continue;
+            }
+        };
+
+        let container = Handle::new(container_id, containment.container_orient);
+        let contained = Handle::new(contained_id, containment.contained_orient);
+
+        let container_len = sequences.node_len(graph, container);
+        let contained_len = sequences.node_len(graph, contained);
+        let pos = containment.pos;
+
+        let overlap = match container_len {
+            Some(len) if pos > len => Err(format!(
+                "position {} is past the end of the {} bp container",
+                pos, len
+            )),
+            _ => overlaps::parse_link_overlap(
+                containment.overlap,
+                container_len.map(|len| len - pos),
+                contained_len,
+            ),
+        };
+        let overlap = match overlap {
+            Ok(overlap) => overlap,
+            Err(reason) => {
+                let kind = LoadErrorKind::InvalidOverlap(reason);
+                rejects.reject(
+                    LoadError::at_line(kind, lt, line, offset)
+                        .with_field("Overlap", containment.overlap),
+                )?;
+                continue;
+            }
+        };
+
+        let tags = if keep_tags {
+            tags::optional_fields(line, tags::CONTAINMENT_FIELDS)
+                .map(|fields| fields.to_vec())
         } else {
             None
+        };
+
+        containments.push(Containment {
+            container,
+            contained,
+            pos,
+            overlap,
+            tags,
+        });
+    }
+
+    Ok(containments)
+}
+
+/// Prints how many lines of each type were in the GFA but weren't
+/// loaded, either because the loader doesn't support them, or
+/// because they don't fit the GFA version being loaded.
+fn report_unused_lines(
+    mmap_gfa: &MmapGFA,
+    indices: &LineIndices,
+    options: &LoadOptions,
+) -> Result<()> {
+    let mut counts: Vec<(u8, usize)> = Vec::new();
+
+    let mut add_count = |line_sig: u8, count: usize| {
+        if count == 0 {
+            return;
         }
-    });
+        match counts.iter_mut().find(|(sig, _)| *sig == line_sig) {
+            Some((_, total)) => *total += count,
+            None => counts.push((line_sig, count)),
+        }
+    };
 
-    graph.create_edges_iter(edges_iter);
+    if indices.gfa2 {
+        add_count(b'L', indices.links.len());
+        add_count(b'J', indices.jumps.len());
+        add_count(b'C', indices.containments.len());
+        add_count(b'U', indices.unordered_groups.len());
+    } else if !options.jumps {
+        add_count(b'J', indices.jumps.len());
+    }
 
+    for &offset in indices.other.iter() {
+        add_count(mmap_gfa.line_at(offset)?[0], 1);
+    }
+
+    if counts.is_empty() {
+        return Ok(());
+    }
+
+    let total: usize = counts.iter().map(|(_, count)| count).sum();
+    let counts = counts
+        .into_iter()
+        .map(|(line_sig, count)| format!("{} {}", count, line_sig as char))
+        .collect::<Vec<_>>();
     eprintln!(
-        "after edges    - space usage: {} bytes",
-        graph.total_bytes()
+        "ignored {} lines that weren't loaded ({})",
+        total,
+        counts.join(", ")
     );
 
-    let mut path_ids: FxHashMap<PathId, (usize, usize)> = FxHashMap::default();
-    path_ids.reserve(indices.paths.len());
+    Ok(())
+}
 
-    eprintln!("adding paths");
-    for &offset in indices.paths.iter() {
-        let line = mmap_gfa.read_line_at(offset)?;
-        let length = line.len();
-        if let Some(path_name) = mmap_gfa.current_line_name() {
-            let path_id = graph.create_path(path_name, false).unwrap();
-            path_ids.insert(path_id, (offset, length));
+/// Loads the segments, links, paths, and walks of a GFA1, using
+/// `id_mapping` to turn segment names into node IDs. With
+/// `IdMapping::Names`, the segments are added to the mapping as
+/// they're loaded.
+fn add_gfa1_lines(
+    graph: &mut PackedGraph,
+    mmap_gfa: &MmapGFA,
+    indices: &LineIndices,
+    mut id_mapping: IdMapping,
+    side: &mut SideTables,
+    rejects: &RejectedLines<'_>,
+) -> Result<IdMapping> {
+    let SideTables {
+        tags,
+        overlaps,
+        sequences,
+    } = side;
+
+    let mut timer = PhaseTimer::start();
+
+    let parser: GFAParser<Vec<u8>, ()> = GFAParser::new();
+
+    eprintln!("adding nodes");
+    for &(offset, _) in indices.segments.iter() {
+        let line = mmap_gfa.line_at(offset)?;
+        let line = &*line;
+        let segment = match parser.parse_gfa_line(line) {
+            Ok(Line::Segment(segment)) => segment,
+            Ok(_) => continue,
+            Err(err) => {
+                rejects.reject(LoadError::from_parse_error(
+                    &err,
+                    LineType::Segment,
+                    line,
+                    offset,
+                ))?;
+                continue;
+            }
+        };
+
+        let error = |kind| {
+            LoadError::at_line(kind, LineType::Segment, line, offset)
+                .with_field("name", &segment.name)
+        };
+
+        // checked before the name is added, so that every name has a
+        // node
+        let length = || tags::length_tag(line, tags::SEGMENT_FIELDS);
+        let sequence = match sequences.sequence(&segment.sequence, length) {
+            Ok(sequence) => sequence,
+            Err(kind) => {
+                rejects.reject(
+                    LoadError::at_line(kind, LineType::Segment, line, offset)
+                        .with_field_name("LN"),
+                )?;
+                continue;
+            }
+        };
+
+        let (id, inserted) = match id_mapping.insert_segment(&segment.name) {
+            Some(inserted) => inserted,
+            None => {
+                rejects.reject(error(LoadErrorKind::InvalidName))?;
+                continue;
+            }
+        };
+        // numeric names aren't tracked by the ID mapping
+        if !inserted || graph.has_node(id) {
+            rejects.reject(error(LoadErrorKind::DuplicateSegment))?;
+            continue;
+        }
+
+        sequences.create_node(graph, id, sequence, length);
+        if let Some(tags) = tags.as_mut() {
+            tags.add_segment(id, line, tags::SEGMENT_FIELDS);
         }
     }
+    eprintln!(
+        "after segments - space usage: {} bytes, {:.2}s",
+        graph.total_bytes(),
+        timer.lap()
+    );
 
-    eprintln!("created path handles");
+    eprintln!("adding edges");
 
-    let mmap_gfa_bytes = mmap_gfa.get_ref();
+    let mut edges = Vec::with_capacity(indices.links.len());
+
+    for &offset in indices.links.iter() {
+        let line = mmap_gfa.line_at(offset)?;
+        let line = &*line;
+        let link = match parser.parse_gfa_line(line) {
+            Ok(Line::Link(link)) => link,
+            Ok(_) => continue,
+            Err(err) => {
+                rejects.reject(LoadError::from_parse_error(
+                    &err,
+                    LineType::Link,
+                    line,
+                    offset,
+                ))?;
+                continue;
+            }
+        };
+
+        let node_id = |name: &[u8]| {
+            id_mapping.segment_id(name).filter(|&id| graph.has_node(id))
+        };
+
+        let from_id = node_id(&link.from_segment);
+        let to_id = node_id(&link.to_segment);
+        let (from_id, to_id) = match (from_id, to_id) {
+            (Some(from_id), Some(to_id)) => (from_id, to_id),
+            (None, _) => {
+                rejects.reject(missing_segment_error(
+                    LineType::Link,
+                    line,
+                    offset,
+                    "From",
+                    &link.from_segment,
+                ))?;
+                continue;
+            }
+            (_, None) => {
+                rejects.reject(missing_segment_error(
+                    LineType::Link,
+                    line,
+                    offset,
+                    "To",
+                    &link.to_segment,
+                ))?;
+                continue;
+            }
+        };
+
+        let from = Handle::new(from_id, link.from_orient);
+        let to = Handle::new(to_id, link.to_orient);
+
+        let edge = Edge(from, to);
+        let overlap = &link.overlap;
+        let node_len = |handle| sequences.node_len(graph, handle);
+        if !add_link_overlap(
+            node_len, edge, overlap, line, offset, overlaps, rejects,
+        )? {
+            continue;
+        }
+        if let Some(tags) = tags.as_mut() {
+            tags.add_link(edge, line, tags::LINK_FIELDS);
+        }
+        edges.push(edge);
+    }
 
-    let parser = mmap_gfa.get_parser();
+    graph.create_edges_iter(edges.into_iter());
 
-    graph.with_all_paths_mut_ctx_chn_new(|path_id, sender, path_ref| {
-        let &(offset, length) = path_ids.get(&path_id).unwrap();
-        let end = offset + length;
-        let line = &mmap_gfa_bytes[offset..end];
-        if let Some(Line::Path(path)) = parser.parse_gfa_line(line).ok() {
-            path_ref.append_handles_iter_chn(
-                sender,
-                path.iter().map(|(node, orient)| {
-                    let node = node + id_offset;
-                    Handle::new(node, orient)
-                }),
-            );
-        }
-    });
+    eprintln!(
+        "after edges    - space usage: {} bytes, {:.2}s",
+        graph.total_bytes(),
+        timer.lap()
+    );
+
+    let path_ids =
+        create_path_handles(graph, mmap_gfa, indices, tags, rejects)?;
+
+    // the graph can't be queried while the paths are filled, and a
+    // numeric ID mapping alone can't tell if a node exists
+    let nodes = NodeSet::from_graph(graph);
+    let node_id = |name: &[u8]| {
+        id_mapping.segment_id(name).filter(|&id| nodes.contains(id))
+    };
+
+    let path_errors = PathErrors::default();
+
+    let path_steps = collect_path_steps(
+        mmap_gfa,
+        &path_ids,
+        &path_errors,
+        |line, line_type, offset| {
+            let mut bad_step = None;
+            let mut missing = None;
+            let steps = match line_type {
+                LineType::Path => PathLine::parse_line(line)
+                    .map_err(|err| {
+                        Box::new(parse_error(err, line_type, line, offset))
+                    })?
+                    .steps()
+                    .filter_map(|step| {
+                        let (name, orient) = step
+                            .map_err(|step| bad_step.get_or_insert(step))
+                            .ok()?;
+                        let node = node_id(name);
+                        if node.is_none() {
+                            missing.get_or_insert(name);
+                        }
+                        Some(Handle::new(node?, orient))
+                    })
+                    .collect(),
+                LineType::Walk => Walk::parse_line(line)
+                    .map_err(|err| {
+                        Box::new(parse_error(err, line_type, line, offset))
+                    })?
+                    .steps()
+                    .filter_map(|step| {
+                        let (name, orient) = step
+                            .map_err(|step| bad_step.get_or_insert(step))
+                            .ok()?;
+                        let node = node_id(name);
+                        if node.is_none() {
+                            missing.get_or_insert(name);
+                        }
+                        Some(Handle::new(node?, orient))
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            match step_error(bad_step, missing, line_type, line, offset) {
+                Some(error) => Err(Box::new(error)),
+                None => Ok(steps),
+            }
+        },
+    )?;
+
+    path_errors.finish(graph, rejects)?;
+    fill_paths(graph, &path_steps);
 
     /*
     graph.with_all_paths_mut_ctx_chn(|path_id, path_ref| {
@@ -136,7 +793,7 @@ pub fn packed_graph_from_mmap(mmap_gfa: &mut MmapGFA) -> Result<PackedGraph> {
         let end = offset + length;
         let line = &mmap_gfa_bytes[offset..end];
 
-        if let Some(Line::Path(path)) = parser.parse_gfa_line(line).ok() {
+        if let Ok(Line::Path(path)) = parser.parse_gfa_line(line) {
             path.iter()
                 .map(|(node, orient)| {
                     let node = node + id_offset;
@@ -156,11 +813,725 @@ pub fn packed_graph_from_mmap(mmap_gfa: &mut MmapGFA) -> Result<PackedGraph> {
     */
 
     eprintln!(
-        "after paths    - space usage: {} bytes",
-        graph.total_bytes()
+        "after paths    - space usage: {} bytes, {:.2}s",
+        graph.total_bytes(),
+        timer.lap()
     );
 
-    Ok(graph)
+    Ok(id_mapping)
+}
+
+/// Parses the steps of every path in parallel with `steps_of`, which
+/// is given the line, type, and offset each path was created from.
+/// Paths with errors, or without any steps, go to `path_errors`
+/// instead, so that only paths with steps are filled.
+fn collect_path_steps<F>(
+    mmap_gfa: &MmapGFA,
+    path_ids: &FxHashMap<PathId, (usize, LineType)>,
+    path_errors: &PathErrors,
+    steps_of: F,
+) -> Result<FxHashMap<PathId, Vec<Handle>>>
+where
+    F: Fn(&[u8], LineType, usize) -> Result<Vec<Handle>, Box<LoadError>> + Sync,
+{
+    path_ids
+        .par_iter()
+        .filter_map(|(&path_id, &(offset, line_type))| {
+            let line = match mmap_gfa.line_at(offset) {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err)),
+            };
+            match steps_of(&line, line_type, offset) {
+                Ok(steps) if steps.is_empty() => {
+                    let kind =
+                        LoadErrorKind::Unsupported("path has no steps".into());
+                    let error =
+                        LoadError::at_line(kind, line_type, &line, offset);
+                    path_errors.push(path_id, error);
+                    None
+                }
+                Ok(steps) => Some(Ok((path_id, steps))),
+                Err(error) => {
+                    path_errors.push(path_id, *error);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Fills the paths with their collected steps, in parallel. Every
+/// path that's left in the graph must have steps, as appending an
+/// empty list of steps breaks the graph.
+fn fill_paths(
+    graph: &mut PackedGraph,
+    path_steps: &FxHashMap<PathId, Vec<Handle>>,
+) {
+    graph.with_all_paths_mut_ctx_chn_new(|path_id, sender, path_ref| {
+        if let Some(steps) = path_steps.get(&path_id) {
+            path_ref.append_handles_iter_chn(sender, steps.iter().copied());
+        }
+    });
+}
+
+/// Collects the errors of the path phase, whose lines are parsed in
+/// parallel and can't return early.
+#[derive(Default)]
+struct PathErrors {
+    errors: Mutex<Vec<(PathId, LoadError)>>,
+}
+
+impl PathErrors {
+    fn push(&self, path_id: PathId, error: LoadError) {
+        self.errors.lock().unwrap().push((path_id, error));
+    }
+
+    /// Rejects the paths with errors in file order, so that a strict
+    /// load fails with the earliest one. When lenient, the rejected
+    /// paths are removed from the graph.
+    fn finish(
+        self,
+        graph: &mut PackedGraph,
+        rejects: &RejectedLines<'_>,
+    ) -> Result<()> {
+        let mut errors = self.errors.into_inner().unwrap();
+        errors.sort_by_key(|(_, error)| error.offset);
+
+        if errors.len() > 1 {
+            eprintln!("{} path lines could not be loaded", errors.len());
+        }
+        for (path_id, error) in errors {
+            rejects.reject(error)?;
+            graph.destroy_path(path_id);
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_error(
+    err: anyhow::Error,
+    line_type: LineType,
+    line: &[u8],
+    offset: usize,
+) -> LoadError {
+    let kind = LoadErrorKind::Parse(err.to_string());
+    LoadError::at_line(kind, line_type, line, offset)
+}
+
+/// Measures the time spent in each phase of a load, which is
+/// reported along with the space usage after the phase.
+struct PhaseTimer {
+    last: Instant,
+}
+
+impl PhaseTimer {
+    fn start() -> Self {
+        Self {
+            last: Instant::now(),
+        }
+    }
+
+    /// Returns the seconds since the previous lap, or since the
+    /// timer was started.
+    fn lap(&mut self) -> f64 {
+        let now = Instant::now();
+        let secs = now.duration_since(self.last).as_secs_f64();
+        self.last = now;
+        secs
+    }
+}
+
+/// What the loaders fill in alongside the graph, for the parts of a
+/// GFA that a `PackedGraph` can't hold.
+struct SideTables {
+    tags: Option<GraphTags>,
+    overlaps: EdgeOverlaps,
+    sequences: SegmentSequences,
+}
+
+/// Applies `MissingSequences` to the segments of a load, and keeps
+/// track of the nodes that were given placeholder sequences.
+struct SegmentSequences {
+    missing: MissingSequences,
+    placeholders: FxHashSet<NodeId>,
+    /// Nodes that were created from a literal `*`, with the length
+    /// of their segment if it's known.
+    literal: FxHashMap<NodeId, Option<usize>>,
+}
+
+impl SegmentSequences {
+    fn new(missing: MissingSequences) -> Self {
+        Self {
+            missing,
+            placeholders: FxHashSet::default(),
+            literal: FxHashMap::default(),
+        }
+    }
+
+    /// Returns the sequence to create a segment's node with. If the
+    /// segment has no sequence, `length` is called to get its length,
+    /// and the error is the reason it can't be loaded.
+    fn sequence<'a>(
+        &mut self,
+        sequence: &'a [u8],
+        length: impl FnOnce() -> Option<usize>,
+    ) -> std::result::Result<Cow<'a, [u8]>, LoadErrorKind> {
+        if sequence != b"*" {
+            return Ok(Cow::Borrowed(sequence));
+        }
+        if self.missing == MissingSequences::Literal {
+            return Ok(Cow::Borrowed(sequence));
+        }
+
+        let length = match length() {
+            Some(0) => {
+                let reason = "segment has no sequence and length 0";
+                return Err(LoadErrorKind::Unsupported(reason.to_string()));
+            }
+            Some(length) => length,
+            None => {
+                let reason = "segment has no sequence or LN tag";
+                return Err(LoadErrorKind::Parse(reason.to_string()));
+            }
+        };
+
+        Ok(Cow::Owned(vec![b'N'; length]))
+    }
+
+    /// Creates node `id` with a sequence from `sequence`. `length`
+    /// is called to get the segment length if the sequence is `*`.
+    fn create_node(
+        &mut self,
+        graph: &mut PackedGraph,
+        id: NodeId,
+        sequence: Cow<'_, [u8]>,
+        length: impl FnOnce() -> Option<usize>,
+    ) {
+        if sequence.as_ref() == b"*" {
+            self.literal.insert(id, length());
+        }
+        let placeholder = matches!(sequence, Cow::Owned(_));
+        if placeholder && self.missing == MissingSequences::LengthOnly {
+            self.placeholders.insert(id);
+        }
+        graph.create_handle(&sequence, id);
+    }
+
+    /// The length of a node's segment. For a node loaded from a
+    /// literal `*`, this is the length from its LN tag or GFA2 slen,
+    /// or `None` if it has none.
+    fn node_len(&self, graph: &PackedGraph, handle: Handle) -> Option<usize> {
+        match self.literal.get(&handle.id()) {
+            Some(&length) => length,
+            None => Some(graph.node_len(handle)),
+        }
+    }
+
+    /// Warns about segments that were loaded with `*` as their
+    /// sequence, and returns the nodes with placeholder sequences and
+    /// the nodes loaded from a literal `*`.
+    fn finish(self) -> (FxHashSet<NodeId>, FxHashMap<NodeId, Option<usize>>) {
+        if !self.literal.is_empty() {
+            eprintln!(
+                "warning: {} segments have no sequence and were loaded as a \
+                 single N, use --missing-sequences to load them with their \
+                 lengths",
+                self.literal.len()
+            );
+        }
+        (self.placeholders, self.literal)
+    }
+}
+
+fn missing_segment_error(
+    line_type: LineType,
+    line: &[u8],
+    offset: usize,
+    field: &str,
+    name: &[u8],
+) -> LoadError {
+    let kind = LoadErrorKind::MissingSegment;
+    LoadError::at_line(kind, line_type, line, offset).with_field(field, name)
+}
+
+/// Checks the overlap of a link against the lengths of the segments
+/// it connects, and stores it if it isn't blunt. Returns false if the
+/// link was rejected.
+fn add_link_overlap(
+    node_len: impl Fn(Handle) -> Option<usize>,
+    edge: Edge,
+    field: &[u8],
+    line: &[u8],
+    offset: usize,
+    overlaps: &mut EdgeOverlaps,
+    rejects: &RejectedLines<'_>,
+) -> Result<bool> {
+    let Edge(from, to) = edge;
+    match overlaps::parse_link_overlap(field, node_len(from), node_len(to)) {
+        Ok(Some(cigar)) => {
+            overlaps.insert(edge, cigar);
+            Ok(true)
+        }
+        Ok(None) => Ok(true),
+        Err(reason) => {
+            let kind = LoadErrorKind::InvalidOverlap(reason);
+            rejects.reject(
+                LoadError::at_line(kind, LineType::Link, line, offset)
+                    .with_field("Overlap", field),
+            )?;
+            Ok(false)
+        }
+    }
+}
+
+/// The error for a path or walk that had a step without an
+/// orientation, or a step on a missing segment, if any.
+fn step_error(
+    bad_step: Option<&[u8]>,
+    missing: Option<&[u8]>,
+    line_type: LineType,
+    line: &[u8],
+    offset: usize,
+) -> Option<LoadError> {
+    let field = match line_type {
+        LineType::Walk => "Walk",
+        LineType::OGroup => "references",
+        _ => "SegmentNames",
+    };
+    if let Some(step) = bad_step {
+        let kind = LoadErrorKind::Parse("step has no orientation".to_string());
+        let error = LoadError::at_line(kind, line_type, line, offset);
+        Some(error.with_field(field, step))
+    } else {
+        missing.map(|name| {
+            missing_segment_error(line_type, line, offset, field, name)
+        })
+    }
+}
+
+/// The node IDs of a graph, as a bitset indexed by ID.
+struct NodeSet {
+    bits: Vec<u64>,
+}
+
+impl NodeSet {
+    fn from_graph(graph: &PackedGraph) -> Self {
+        let max_id = u64::from(graph.max_node_id()) as usize;
+        let mut bits = vec![0u64; max_id / 64 + 1];
+        for handle in graph.handles() {
+            let id = u64::from(handle.id()) as usize;
+            bits[id / 64] |= 1 << (id % 64);
+        }
+        Self { bits }
+    }
+
+    #[inline]
+    fn contains(&self, id: NodeId) -> bool {
+        let id = u64::from(id) as usize;
+        self.bits
+            .get(id / 64)
+            .is_some_and(|word| word & (1 << (id % 64)) != 0)
+    }
+}
+
+fn read_header_lines(
+    mmap_gfa: &MmapGFA,
+    indices: &LineIndices,
+) -> Result<Vec<Vec<u8>>> {
+    let mut headers = Vec::with_capacity(indices.headers.len());
+    for &offset in indices.headers.iter() {
+        headers.push(mmap_gfa.line_at(offset)?.to_vec());
+    }
+    Ok(headers)
+}
+
+fn add_gfa2_lines(
+    graph: &mut PackedGraph,
+    mmap_gfa: &MmapGFA,
+    indices: &LineIndices,
+    mut id_mapping: IdMapping,
+    side: &mut SideTables,
+    rejects: &RejectedLines<'_>,
+) -> Result<IdMapping> {
+    let SideTables {
+        tags,
+        overlaps,
+        sequences,
+    } = side;
+
+    let mut timer = PhaseTimer::start();
+
+    eprintln!("adding nodes");
+    for &(offset, _) in indices.segments.iter() {
+        let line = mmap_gfa.line_at(offset)?;
+        let line = &*line;
+        let segment = match gfa2::Segment::parse_line(line) {
+            Ok(segment) => segment,
+            Err(err) => {
+                rejects.reject(parse_error(
+                    err,
+                    LineType::Segment,
+                    line,
+                    offset,
+                ))?;
+                continue;
+            }
+        };
+
+        let segment_error = |kind| {
+            LoadError::at_line(kind, LineType::Segment, line, offset)
+                .with_field("sid", segment.name)
+        };
+
+        let length = || Some(segment.length);
+        let sequence = match sequences.sequence(segment.sequence, length) {
+            Ok(sequence) => sequence,
+            Err(kind) => {
+                rejects.reject(segment_error(kind))?;
+                continue;
+            }
+        };
+
+        let (id, inserted) = match id_mapping.insert_segment(segment.name) {
+            Some(inserted) => inserted,
+            None => {
+                rejects.reject(segment_error(LoadErrorKind::InvalidName))?;
+                continue;
+            }
+        };
+        // numeric names aren't tracked by the ID mapping
+        if !inserted || graph.has_node(id) {
+            rejects.reject(segment_error(LoadErrorKind::DuplicateSegment))?;
+            continue;
+        }
+        sequences.create_node(graph, id, sequence, length);
+        if let Some(tags) = tags.as_mut() {
+            tags.add_segment(id, line, tags::GFA2_SEGMENT_FIELDS);
+        }
+    }
+    eprintln!(
+        "after segments - space usage: {} bytes, {:.2}s",
+        graph.total_bytes(),
+        timer.lap()
+    );
+
+    eprintln!("adding edges");
+
+    let mut edges = Vec::with_capacity(indices.edges.len());
+    let mut skipped_edges = 0;
+
+    // groups can refer to edges and other groups, as well as segments
+    let mut other_references: FxHashSet<Vec<u8>> = FxHashSet::default();
+    for &offset in indices
+        .ordered_groups
+        .iter()
+        .chain(indices.unordered_groups.iter())
+    {
+        if let Some(name) = mmap_gfa::line_name(&mmap_gfa.line_at(offset)?) {
+            other_references.insert(name.to_vec());
+        }
+    }
+
+    for &offset in indices.edges.iter() {
+        let line = mmap_gfa.line_at(offset)?;
+        let line = &*line;
+        let edge = match gfa2::Edge::parse_line(line) {
+            Ok(edge) => edge,
+            Err(err) => {
+                rejects.reject(parse_error(
+                    err,
+                    LineType::Edge,
+                    line,
+                    offset,
+                ))?;
+                continue;
+            }
+        };
+
+        other_references.insert(edge.name.to_vec());
+
+        let sides = if let Some(sides) = edge.dovetail_sides() {
+            sides
+        } else {
+            skipped_edges += 1;
+            continue;
+        };
+
+        // only overlaps that are exact matches of the same length on
+        // both segments can be written as L lines
+        let overlap = if edge.is_blunt() {
+            None
+        } else if let Some(overlap) = edge.match_overlap() {
+            Some(overlap)
+        } else {
+            let (len1, len2) = edge.interval_lens();
+            let reason = format!(
+                "dovetail overlap intervals of {} and {} bp",
+                len1, len2
+            );
+            let kind = LoadErrorKind::Unsupported(reason);
+            rejects.reject(
+                LoadError::at_line(kind, LineType::Edge, line, offset)
+                    .with_field("eid", edge.name),
+            )?;
+            continue;
+        };
+
+        let node_id = |name: &[u8]| {
+            id_mapping.segment_id(name).filter(|&id| graph.has_node(id))
+        };
+
+        let id1 = node_id(edge.sid1.0);
+        let id2 = node_id(edge.sid2.0);
+        let (id1, id2) = match (id1, id2) {
+            (Some(id1), Some(id2)) => (id1, id2),
+            (None, _) => {
+                rejects.reject(missing_segment_error(
+                    LineType::Edge,
+                    line,
+                    offset,
+                    "sid1",
+                    edge.sid1.0,
+                ))?;
+                continue;
+            }
+            (_, None) => {
+                rejects.reject(missing_segment_error(
+                    LineType::Edge,
+                    line,
+                    offset,
+                    "sid2",
+                    edge.sid2.0,
+                ))?;
+                continue;
+            }
+        };
+
+        let graph_edge = gfa2::dovetail_edge(sides, id1, id2);
+        if let Some(overlap) = overlap {
+            let Edge(from, to) = graph_edge;
+            let from_len = sequences.node_len(graph, from);
+            let to_len = sequences.node_len(graph, to);
+            if let Err(reason) =
+                overlaps::check_overlap_lengths(&overlap, from_len, to_len)
+            {
+                let kind = LoadErrorKind::InvalidOverlap(reason);
+                rejects.reject(
+                    LoadError::at_line(kind, LineType::Edge, line, offset)
+                        .with_field("alignment", edge.alignment),
+                )?;
+                continue;
+            }
+            overlaps.insert(graph_edge, overlap);
+        }
+        if let Some(tags) = tags.as_mut() {
+            tags.add_link(graph_edge, line, tags::GFA2_EDGE_FIELDS);
+        }
+        edges.push(graph_edge);
+    }
+
+    graph.create_edges_iter(edges.into_iter());
+
+    if skipped_edges > 0 {
+        eprintln!("skipped {} E lines that aren't dovetails", skipped_edges);
+    }
+
+    eprintln!(
+        "after edges    - space usage: {} bytes, {:.2}s",
+        graph.total_bytes(),
+        timer.lap()
+    );
+
+    let path_ids =
+        create_path_handles(graph, mmap_gfa, indices, tags, rejects)?;
+
+    let nodes = NodeSet::from_graph(graph);
+    let node_id = |name: &[u8]| {
+        id_mapping.segment_id(name).filter(|&id| nodes.contains(id))
+    };
+
+    let path_errors = PathErrors::default();
+
+    let path_steps = collect_path_steps(
+        mmap_gfa,
+        &path_ids,
+        &path_errors,
+        |line, line_type, offset| {
+            if line_type != LineType::OGroup {
+                return Ok(Vec::new());
+            }
+            // references to edges and groups are skipped, but anything
+            // else that isn't a segment is an error
+            let mut bad_step = None;
+            let mut missing = None;
+            let steps = gfa2::Group::parse_ordered(line)
+                .map_err(|err| {
+                    Box::new(parse_error(err, line_type, line, offset))
+                })?
+                .ordered_references()
+                .filter_map(|reference| {
+                    let (name, orient) = match reference {
+                        Ok(reference) => reference,
+                        Err(reference) => {
+                            bad_step.get_or_insert(reference);
+                            return None;
+                        }
+                    };
+                    let node = node_id(name);
+                    if node.is_none() && !other_references.contains(name) {
+                        missing.get_or_insert(name);
+                    }
+                    Some(Handle::new(node?, orient))
+                })
+                .collect();
+            match step_error(bad_step, missing, line_type, line, offset) {
+                Some(error) => Err(Box::new(error)),
+                None => Ok(steps),
+            }
+        },
+    )?;
+
+    path_errors.finish(graph, rejects)?;
+    fill_paths(graph, &path_steps);
+
+    eprintln!(
+        "after paths    - space usage: {} bytes, {:.2}s",
+        graph.total_bytes(),
+        timer.lap()
+    );
+
+    Ok(id_mapping)
+}
+
+/// Creates an empty path for each P, W, and O line, and returns the
+/// offset and type of the line each path was created from.
+fn create_path_handles(
+    graph: &mut PackedGraph,
+    mmap_gfa: &MmapGFA,
+    indices: &LineIndices,
+    tags: &mut Option<GraphTags>,
+    rejects: &RejectedLines<'_>,
+) -> Result<FxHashMap<PathId, (usize, LineType)>> {
+    let mut path_ids: FxHashMap<PathId, (usize, LineType)> =
+        FxHashMap::default();
+    path_ids.reserve(indices.paths.len() + indices.walks.len());
+
+    // returns `None` if the path was rejected
+    let create_path = |graph: &mut PackedGraph,
+                       name: &[u8],
+                       line_type,
+                       line: &[u8],
+                       offset|
+     -> Result<Option<PathId>> {
+        if let Some(path_id) = graph.create_path(name, false) {
+            return Ok(Some(path_id));
+        }
+        let kind = LoadErrorKind::DuplicatePath;
+        rejects.reject(
+            LoadError::at_line(kind, line_type, line, offset)
+                .with_field("name", name),
+        )?;
+        Ok(None)
+    };
+
+    eprintln!("adding paths");
+    for &offset in indices.paths.iter() {
+        let line = mmap_gfa.line_at(offset)?;
+        let line = &*line;
+        let path = match PathLine::parse_line(line) {
+            Ok(path) => path,
+            Err(err) => {
+                rejects.reject(parse_error(
+                    err,
+                    LineType::Path,
+                    line,
+                    offset,
+                ))?;
+                continue;
+            }
+        };
+        let path_id = match create_path(
+            graph,
+            path.name,
+            LineType::Path,
+            line,
+            offset,
+        )? {
+            Some(path_id) => path_id,
+            None => continue,
+        };
+        path_ids.insert(path_id, (offset, LineType::Path));
+        if let Some(tags) = tags.as_mut() {
+            tags.add_path(path_id, line, tags::PATH_FIELDS);
+        }
+    }
+
+    for &offset in indices.ordered_groups.iter() {
+        let line = mmap_gfa.line_at(offset)?;
+        let line = &*line;
+        let group = match gfa2::Group::parse_ordered(line) {
+            Ok(group) => group,
+            Err(err) => {
+                rejects.reject(parse_error(
+                    err,
+                    LineType::OGroup,
+                    line,
+                    offset,
+                ))?;
+                continue;
+            }
+        };
+        let path_id = match create_path(
+            graph,
+            group.name,
+            LineType::OGroup,
+            line,
+            offset,
+        )? {
+            Some(path_id) => path_id,
+            None => continue,
+        };
+        path_ids.insert(path_id, (offset, LineType::OGroup));
+        if let Some(tags) = tags.as_mut() {
+            tags.add_path(path_id, line, tags::GFA2_GROUP_FIELDS);
+        }
+    }
+
+    if !indices.walks.is_empty() {
+        eprintln!("adding walks");
+    }
+    for &offset in indices.walks.iter() {
+        let line = mmap_gfa.line_at(offset)?;
+        let line = &*line;
+        let walk = match Walk::parse_line(line) {
+            Ok(walk) => walk,
+            Err(err) => {
+                rejects.reject(parse_error(
+                    err,
+                    LineType::Walk,
+                    line,
+                    offset,
+                ))?;
+                continue;
+            }
+        };
+        let path_name = walk.path_name();
+        let path_id =
+            match create_path(graph, &path_name, LineType::Walk, line, offset)?
+            {
+                Some(path_id) => path_id,
+                None => continue,
+            };
+        path_ids.insert(path_id, (offset, LineType::Walk));
+        if let Some(tags) = tags.as_mut() {
+            tags.add_path(path_id, line, tags::WALK_FIELDS);
+        }
+    }
+
+    eprintln!("created path handles");
+
+    Ok(path_ids)
 }
 
 /*
@@ -0,0 +1,105 @@
+use handlegraph::handle::Edge;
+
+use gfa::gfa::Orientation;
+
+use anyhow::{anyhow, Result};
+
+use bstr::ByteSlice;
+
+use fxhash::FxHashMap;
+
+use std::fmt;
+
+use crate::fields::{parse_orientation, Fields};
+
+/// A GFA 1.2 jump (J) line, borrowing the line it was parsed from.
+/// A jump connects two segments like a link, but across a gap of
+/// unknown sequence, such as a scaffolding gap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Jump<'a> {
+    pub from_segment: &'a [u8],
+    pub from_orient: Orientation,
+    pub to_segment: &'a [u8],
+    pub to_orient: Orientation,
+    pub distance: Gap,
+}
+
+impl<'a> Jump<'a> {
+    pub fn parse_line(line: &'a [u8]) -> Result<Self> {
+        let mut fields = Fields::new(line, "J")?;
+        let from_segment = fields.next("From")?;
+        let from_orient =
+            parse_orientation(fields.next("FromOrient")?, "FromOrient")?;
+        let to_segment = fields.next("To")?;
+        let to_orient =
+            parse_orientation(fields.next("ToOrient")?, "ToOrient")?;
+        let distance = Gap::parse(fields.next("Distance")?)?;
+
+        Ok(Jump {
+            from_segment,
+            from_orient,
+            to_segment,
+            to_orient,
+            distance,
+        })
+    }
+}
+
+/// The estimated distance of a jump, in bases, or `None` if it's
+/// unknown (`*`). Negative distances are overlaps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gap(pub Option<i64>);
+
+impl Gap {
+    pub fn parse(field: &[u8]) -> Result<Self> {
+        if field == b"*" {
+            return Ok(Gap(None));
+        }
+        let distance = field
+            .to_str()
+            .ok()
+            .and_then(|field| field.parse::<i64>().ok())
+            .ok_or_else(|| anyhow!("invalid distance `{}`", field.as_bstr()))?;
+        Ok(Gap(Some(distance)))
+    }
+}
+
+impl fmt::Display for Gap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(distance) => write!(f, "{}", distance),
+            None => write!(f, "*"),
+        }
+    }
+}
+
+/// Side table marking the edges of a loaded graph that came from J
+/// lines, with the gap each one spans, so that they can be written
+/// back out as jumps rather than links.
+#[derive(Debug, Default, Clone)]
+pub struct JumpEdges {
+    gaps: FxHashMap<Edge, Gap>,
+}
+
+impl JumpEdges {
+    pub fn insert(&mut self, edge: Edge, gap: Gap) {
+        self.gaps.insert(edge, gap);
+    }
+
+    /// Looks up the gap of an edge in either orientation.
+    pub fn get(&self, edge: Edge) -> Option<Gap> {
+        let Edge(left, right) = edge;
+        self.gaps
+            .get(&edge)
+            .or_else(|| self.gaps.get(&Edge(right.flip(), left.flip())))
+            .copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.gaps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.gaps.is_empty()
+    }
+}
@@ -1,3 +1,22 @@
+pub mod bluntify;
+pub mod capacity;
+pub mod commands;
+pub mod containments;
+pub mod fields;
+pub mod gfa2;
+pub mod gfa_writer;
+pub mod gfai;
+pub mod input;
 pub mod interface;
 pub mod io;
+pub mod jumps;
+pub mod load_error;
 pub mod mmap_gfa;
+pub mod node_ids;
+pub mod overlaps;
+pub mod path_line;
+pub mod path_selection;
+pub mod segment_names;
+pub mod tags;
+pub mod validation;
+pub mod walks;
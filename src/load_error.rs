@@ -0,0 +1,320 @@
+use gfa::parser::{ParseError, ParseFieldError};
+
+use bstr::ByteSlice;
+
+use anyhow::Result;
+
+use std::fmt;
+use std::io::Write;
+use std::sync::Mutex;
+
+use crate::{input::GfaData, mmap_gfa::LineType};
+
+/// Lines are cut to this many bytes in error messages.
+const EXCERPT_LEN: usize = 80;
+
+/// What was wrong with the record a `LoadError` points to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadErrorKind {
+    /// The line couldn't be parsed.
+    Parse(String),
+    /// A segment name couldn't be used as or mapped to a node ID.
+    InvalidName,
+    DuplicateSegment,
+    DuplicatePath,
+    /// The line refers to a segment that doesn't exist.
+    MissingSegment,
+    /// A path has this many pairs of consecutive steps that aren't
+    /// connected by a link.
+    MissingLink(usize),
+    /// A link or GFA2 dovetail edge with an overlap that fits the
+    /// segments, reported by validation because the graph doesn't
+    /// spell it as blunt.
+    NonBluntOverlap(String),
+    /// An overlap that isn't a CIGAR, doesn't fit the segments, or
+    /// covers different lengths of the two segments of a GFA2 edge.
+    InvalidOverlap(String),
+    /// A segment sequence with characters that aren't IUPAC codes.
+    InvalidSequence,
+    /// The line is valid GFA, but can't be loaded into a graph.
+    Unsupported(String),
+}
+
+impl LoadErrorKind {
+    /// A short identifier for the kind of error, used in JSON output.
+    pub fn code(&self) -> &'static str {
+        match self {
+            LoadErrorKind::Parse(_) => "parse_error",
+            LoadErrorKind::InvalidName => "invalid_name",
+            LoadErrorKind::DuplicateSegment => "duplicate_segment",
+            LoadErrorKind::DuplicatePath => "duplicate_path",
+            LoadErrorKind::MissingSegment => "missing_segment",
+            LoadErrorKind::MissingLink(_) => "missing_link",
+            LoadErrorKind::NonBluntOverlap(_) => "non_blunt_overlap",
+            LoadErrorKind::InvalidOverlap(_) => "invalid_overlap",
+            LoadErrorKind::InvalidSequence => "invalid_sequence",
+            LoadErrorKind::Unsupported(_) => "unsupported",
+        }
+    }
+}
+
+impl fmt::Display for LoadErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadErrorKind::Parse(reason) => {
+                write!(f, "parse error: {}", reason)
+            }
+            LoadErrorKind::InvalidName => write!(f, "invalid segment name"),
+            LoadErrorKind::DuplicateSegment => {
+                write!(f, "duplicate segment name")
+            }
+            LoadErrorKind::DuplicatePath => write!(f, "duplicate path name"),
+            LoadErrorKind::MissingSegment => write!(f, "undefined segment"),
+            LoadErrorKind::MissingLink(count) => {
+                write!(f, "{} pairs of consecutive steps without a link", count)
+            }
+            LoadErrorKind::NonBluntOverlap(overlap) => {
+                write!(f, "non-blunt overlap {}", overlap)
+            }
+            LoadErrorKind::InvalidOverlap(reason) => {
+                write!(f, "invalid overlap: {}", reason)
+            }
+            LoadErrorKind::InvalidSequence => {
+                write!(f, "invalid sequence character")
+            }
+            LoadErrorKind::Unsupported(reason) => {
+                write!(f, "unsupported: {}", reason)
+            }
+        }
+    }
+}
+
+/// An error in a specific record of a GFA, with enough context to
+/// find the record in a large file. The loaders return these wrapped
+/// in an `anyhow::Error`, from which they can be recovered with
+/// `downcast_ref::<LoadError>()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadError {
+    pub kind: LoadErrorKind,
+    pub line_type: LineType,
+    /// 1-based line number.
+    pub line_number: usize,
+    /// Byte offset of the start of the line.
+    pub offset: usize,
+    /// Name of the offending field, if known.
+    pub field: Option<String>,
+    /// Value of the offending field, if known.
+    pub value: Option<String>,
+    /// The start of the line.
+    pub excerpt: String,
+}
+
+impl LoadError {
+    /// Creates an error for `line`, which starts at `offset`, without
+    /// finding its line number. When there are many errors, it's
+    /// faster to fill in the line numbers with `set_line_numbers`.
+    pub fn at_line(
+        kind: LoadErrorKind,
+        line_type: LineType,
+        line: &[u8],
+        offset: usize,
+    ) -> Self {
+        let line = match line.find_byte(b'\n') {
+            Some(end) => &line[..end],
+            None => line,
+        };
+        let line = line.trim_end_with(|c| c == '\r');
+
+        let mut excerpt = line[..line.len().min(EXCERPT_LEN)]
+            .to_str_lossy()
+            .replace('\t', " ");
+        if line.len() > EXCERPT_LEN {
+            excerpt.push_str("...");
+        }
+
+        Self {
+            kind,
+            line_type,
+            line_number: 0,
+            offset,
+            field: None,
+            value: None,
+            excerpt,
+        }
+    }
+
+    /// Creates a parse error for `line`, which starts at `offset`,
+    /// from a GFA parser error, pulling out the invalid field if the
+    /// parser reported one. The line number isn't set.
+    pub fn from_parse_error(
+        err: &ParseError,
+        line_type: LineType,
+        line: &[u8],
+        offset: usize,
+    ) -> Self {
+        let field_err = match err {
+            ParseError::InvalidLine(field_err, _) => Some(field_err),
+            ParseError::InvalidField(field_err) => Some(field_err),
+            _ => None,
+        };
+
+        let reason = match field_err {
+            Some(field_err) => field_err.to_string(),
+            None => err.to_string(),
+        };
+
+        let kind = LoadErrorKind::Parse(reason);
+        let error = Self::at_line(kind, line_type, line, offset);
+
+        match field_err {
+            Some(ParseFieldError::InvalidField(name)) => {
+                error.with_field_name(name)
+            }
+            _ => error,
+        }
+    }
+
+    pub fn with_field_name(mut self, name: &str) -> Self {
+        self.field = Some(name.to_string());
+        self
+    }
+
+    pub fn with_field(mut self, name: &str, value: &[u8]) -> Self {
+        self.field = Some(name.to_string());
+        self.value = Some(value.to_str_lossy().into_owned());
+        self
+    }
+}
+
+/// Sorts the errors by offset and sets their line numbers, in a
+/// single pass over the data of the file.
+pub fn set_line_numbers(
+    errors: &mut [LoadError],
+    data: &GfaData,
+) -> Result<()> {
+    errors.sort_by_key(|error| error.offset);
+
+    let mut line_number = 1;
+    let mut counted = 0;
+    for error in errors.iter_mut() {
+        line_number += data.count_lines(counted, error.offset)?;
+        counted = error.offset;
+        error.line_number = line_number;
+    }
+    Ok(())
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} line {} (byte offset {}): {}",
+            self.line_type.line_sig() as char,
+            self.line_number,
+            self.offset,
+            self.kind
+        )?;
+
+        match (self.field.as_ref(), self.value.as_ref()) {
+            (Some(field), Some(value)) => {
+                write!(f, " in field {} `{}`", field, value)?
+            }
+            (Some(field), None) => write!(f, " in field {}", field)?,
+            _ => (),
+        }
+
+        write!(f, "\n    {}", self.excerpt)
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// What the loader does with a record that can't be loaded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Abort on the first bad record.
+    #[default]
+    Strict,
+    /// Skip bad records and keep loading, collecting the errors.
+    Lenient,
+}
+
+/// Applies an `ErrorPolicy` to the errors of a load, and collects
+/// the rejected records when lenient. `data` should be the entire
+/// file, so that line numbers can be filled in.
+pub struct RejectedLines<'a> {
+    policy: ErrorPolicy,
+    data: &'a GfaData,
+    errors: Mutex<Vec<LoadError>>,
+}
+
+impl<'a> RejectedLines<'a> {
+    pub fn new(policy: ErrorPolicy, data: &'a GfaData) -> Self {
+        Self {
+            policy,
+            data,
+            errors: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the error if the policy is strict, otherwise records
+    /// it so the caller can skip the record.
+    pub fn reject(&self, mut error: LoadError) -> Result<()> {
+        match self.policy {
+            ErrorPolicy::Strict => {
+                error.line_number = self.data.count_lines(0, error.offset)? + 1;
+                Err(error.into())
+            }
+            ErrorPolicy::Lenient => {
+                self.errors.lock().unwrap().push(error);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the rejected records, sorted by offset and with their
+    /// line numbers set.
+    pub fn finish(self) -> Result<Vec<LoadError>> {
+        let mut errors = self.errors.into_inner().unwrap();
+        set_line_numbers(&mut errors, self.data)?;
+        Ok(errors)
+    }
+}
+
+/// The number of rejected records of each line type, in the order
+/// the line types are loaded.
+pub fn rejected_counts(errors: &[LoadError]) -> Vec<(LineType, usize)> {
+    let mut counts: Vec<(LineType, usize)> = Vec::new();
+    for error in errors {
+        match counts.iter_mut().find(|(lt, _)| *lt == error.line_type) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((error.line_type, 1)),
+        }
+    }
+    counts.sort_by_key(|(lt, _)| *lt as u8);
+    counts
+}
+
+/// Writes the rejected records as TSV, one line per record, with
+/// the line type, line number, byte offset, error kind, field and
+/// value, and a description of the error.
+pub fn write_rejected_report<W: Write>(
+    errors: &[LoadError],
+    out: &mut W,
+) -> std::io::Result<()> {
+    writeln!(out, "#type\tline\toffset\terror\tfield\tvalue\tmessage")?;
+    for error in errors {
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            error.line_type.line_sig() as char,
+            error.line_number,
+            error.offset,
+            error.kind.code(),
+            error.field.as_deref().unwrap_or("*"),
+            error.value.as_deref().unwrap_or("*").replace('\t', " "),
+            error.kind.to_string().replace('\t', " "),
+        )?;
+    }
+    Ok(())
+}
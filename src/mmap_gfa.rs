@@ -4,144 +4,423 @@ use anyhow::{bail, Result};
 
 use memmap::Mmap;
 
-use std::fs::File;
-use std::io::prelude::*;
-
 use bstr::ByteSlice;
 
+use rayon::prelude::*;
+
+use crate::{
+    gfai,
+    input::{self, GfaData, LineRef},
+};
+
 pub struct MmapGFA {
-    pub cursor: std::io::Cursor<Mmap>,
+    pub data: GfaData,
+    /// Offset of the cursor used by `next_line` and `seek_next`.
+    pub position: usize,
     pub line_buf: Vec<u8>,
     pub current_line_len: usize,
     // pub current_line_type: LineType,
     pub last_buf_offset: usize,
     pub parser: GFAParser<usize, ()>,
+    /// Path of the input file, if it's a regular file that can have
+    /// a sidecar index.
+    pub path: Option<String>,
 }
 
-// pub struct MmapGFALines<'a> {
-//     mmap_gfa: &'a mut MmapGFA,
-// }
+/// Iterator over the parsed lines of a single type, created by
+/// `MmapGFA::typed_lines`.
+pub struct MmapGFALines<'a> {
+    mmap_gfa: &'a mut MmapGFA,
+    line_type: LineType,
+}
+
+impl<'a> Iterator for MmapGFALines<'a> {
+    type Item = Result<Line<usize, ()>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.mmap_gfa.seek_next(self.line_type) {
+            Ok(Some(_)) => Some(self.mmap_gfa.parse_current_line()),
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
 
-// impl<'a> Iterator for MmapGFALines<'a> {
-//     type Item = Vec<u8>;
+/// Strips the line terminator, either `\n` or `\r\n`, from the end
+/// of a line. A last line without a newline is returned as is.
+#[inline]
+pub fn trim_line_end(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
 
-//     #[inline]
-//     fn next(&mut self) -> Option<Self::Item> {
-//         let line = self.mmap_gfa.next_line().ok()?;
-//         let line = line.to_owned();
-//         Some(line)
-//     }
-// }
+/// True if the line is empty or only whitespace.
+#[inline]
+fn is_blank(line: &[u8]) -> bool {
+    line.iter().all(|c| c.is_ascii_whitespace())
+}
+
+/// Returns the second field of a line, which is the name for the line
+/// types that have one.
+pub fn line_name(line: &[u8]) -> Option<&[u8]> {
+    let line = trim_line_end(line);
+    let mut iter = line.split_str("\t");
+    let _lt = iter.next()?;
+    let name = iter.next()?;
+    Some(name)
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LineType {
     Segment,
     Link,
+    Jump,
+    Containment,
     Path,
+    Walk,
+    Edge,
+    OGroup,
+    UGroup,
+}
+
+impl LineType {
+    /// The first byte of lines of this type.
+    pub fn line_sig(&self) -> u8 {
+        match self {
+            LineType::Segment => b'S',
+            LineType::Link => b'L',
+            LineType::Jump => b'J',
+            LineType::Containment => b'C',
+            LineType::Path => b'P',
+            LineType::Walk => b'W',
+            LineType::Edge => b'E',
+            LineType::OGroup => b'O',
+            LineType::UGroup => b'U',
+        }
+    }
+}
+
+/// Totals over the S lines of a file, collected while indexing so
+/// that loading doesn't need a separate pass over the segments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SegmentStats {
+    /// True if every segment name is an unsigned integer.
+    pub numeric_names: bool,
+    /// The smallest and largest segment names, if they're numeric.
+    pub min_id: usize,
+    pub max_id: usize,
+    /// The sum and maximum of the segment lengths, which are the
+    /// `slen` field in GFA2 and the sequence length in GFA1, with
+    /// `*` counting as 0.
+    pub total_length: usize,
+    pub max_length: usize,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+impl Default for SegmentStats {
+    fn default() -> Self {
+        Self {
+            numeric_names: true,
+            min_id: usize::MAX,
+            max_id: 0,
+            total_length: 0,
+            max_length: 0,
+        }
+    }
+}
+
+impl SegmentStats {
+    fn add_line(&mut self, line: &[u8]) {
+        let mut fields = trim_line_end(line).split_str("\t").skip(1);
+        let name = fields.next().unwrap_or_default();
+
+        if self.numeric_names {
+            let id = name.to_str().ok().and_then(|name| name.parse().ok());
+            match id {
+                Some(id) => {
+                    self.min_id = self.min_id.min(id);
+                    self.max_id = self.max_id.max(id);
+                }
+                None => self.numeric_names = false,
+            }
+        }
+
+        // a GFA1 sequence can't be a number, so a numeric third field
+        // is the length of a GFA2 segment
+        let field = fields.next().unwrap_or_default();
+        let length = match field.to_str().ok().and_then(|f| f.parse().ok()) {
+            Some(length) => length,
+            None if field == b"*" => 0,
+            None => field.len(),
+        };
+        self.total_length += length;
+        self.max_length = self.max_length.max(length);
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.numeric_names &= other.numeric_names;
+        self.min_id = self.min_id.min(other.min_id);
+        self.max_id = self.max_id.max(other.max_id);
+        self.total_length += other.total_length;
+        self.max_length = self.max_length.max(other.max_length);
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct LineIndices {
     // pub segments: Vec<usize>,
     pub segments: Vec<(usize, usize)>,
+    pub segment_stats: SegmentStats,
     pub links: Vec<usize>,
+    pub jumps: Vec<usize>,
+    pub containments: Vec<usize>,
     pub paths: Vec<usize>,
+    pub walks: Vec<usize>,
+    pub edges: Vec<usize>,
+    pub ordered_groups: Vec<usize>,
+    pub unordered_groups: Vec<usize>,
+    /// H lines and `#` comment lines, in file order.
+    pub headers: Vec<usize>,
+    /// Lines of any other type, such as GFA2 fragments and gaps,
+    /// which are never loaded.
+    pub other: Vec<usize>,
+    /// True if the header declares GFA2, or there are GFA2-only
+    /// lines (E, O, U) in the file.
+    pub gfa2: bool,
+}
+
+/// Chunks smaller than this aren't worth indexing on their own.
+const INDEX_CHUNK_MIN: usize = 1 << 20;
+
+impl LineIndices {
+    /// Indexes the lines that start in `start..end`, where `start`
+    /// must be the start of a line.
+    fn index_chunk(data: &GfaData, start: usize, end: usize) -> Result<Self> {
+        let mut res = Self::default();
+
+        let mut line_start = start;
+
+        while line_start < end {
+            let line = data.line(line_start)?;
+            let line = &*line;
+            let length = line.len();
+
+            // blank lines, including `\r\n` ones, fall through to the
+            // last arm and are skipped
+            match line[0] {
+                b'S' => {
+                    res.segments.push((line_start, length));
+                    res.segment_stats.add_line(line);
+                }
+                b'L' => {
+                    res.links.push(line_start);
+                }
+                b'J' => {
+                    res.jumps.push(line_start);
+                }
+                b'C' => {
+                    res.containments.push(line_start);
+                }
+                b'P' => {
+                    res.paths.push(line_start);
+                }
+                b'W' => {
+                    res.walks.push(line_start);
+                }
+                b'H' => {
+                    if line.find(b"VN:Z:2").is_some() {
+                        res.gfa2 = true;
+                    }
+                    res.headers.push(line_start);
+                }
+                b'#' => {
+                    res.headers.push(line_start);
+                }
+                b'E' => {
+                    res.edges.push(line_start);
+                    res.gfa2 = true;
+                }
+                b'O' => {
+                    res.ordered_groups.push(line_start);
+                    res.gfa2 = true;
+                }
+                b'U' => {
+                    res.unordered_groups.push(line_start);
+                    res.gfa2 = true;
+                }
+                _ if !is_blank(line) => {
+                    res.other.push(line_start);
+                }
+                _ => (),
+            }
+
+            line_start += length;
+        }
+
+        Ok(res)
+    }
+
+    /// Appends the indices of a chunk that comes after this one.
+    fn append(&mut self, mut other: Self) {
+        self.segments.append(&mut other.segments);
+        self.segment_stats.merge(&other.segment_stats);
+        self.links.append(&mut other.links);
+        self.jumps.append(&mut other.jumps);
+        self.containments.append(&mut other.containments);
+        self.paths.append(&mut other.paths);
+        self.walks.append(&mut other.walks);
+        self.edges.append(&mut other.edges);
+        self.ordered_groups.append(&mut other.ordered_groups);
+        self.unordered_groups.append(&mut other.unordered_groups);
+        self.headers.append(&mut other.headers);
+        self.other.append(&mut other.other);
+        self.gfa2 |= other.gfa2;
+    }
 }
 
 impl MmapGFA {
+    /// Maps the GFA at `path`, which may be gzip or BGZF compressed.
     pub fn new(path: &str) -> Result<Self> {
-        let file = File::open(path)?;
-        let mmap = unsafe { Mmap::map(&file)? };
+        let data = input::map_gfa(path)?;
+        let mut mmap_gfa = Self::from_data(data);
+
+        let is_file = std::fs::metadata(path)
+            .map(|metadata| metadata.is_file())
+            .unwrap_or(false);
+        if path != "-" && is_file {
+            mmap_gfa.path = Some(path.to_string());
+        }
+
+        Ok(mmap_gfa)
+    }
+
+    pub fn from_mmap(mmap: Mmap) -> Self {
+        Self::from_data(GfaData::Mapped(mmap))
+    }
 
-        let cursor = std::io::Cursor::new(mmap);
+    pub fn from_data(data: GfaData) -> Self {
         let line_buf = Vec::with_capacity(1024);
         let current_line_len = 0;
         let last_buf_offset = 0;
 
         let parser = GFAParser::new();
 
-        Ok(Self {
-            cursor,
+        Self {
+            data,
+            position: 0,
             line_buf,
             current_line_len,
             last_buf_offset,
             parser,
-        })
+            path: None,
+        }
     }
 
-    pub fn get_ref(&self) -> &[u8] {
-        self.cursor.get_ref().as_ref()
+    /// The uncompressed data of the GFA.
+    pub fn data(&self) -> &GfaData {
+        &self.data
     }
 
     pub fn get_parser(&self) -> &GFAParser<usize, ()> {
         &self.parser
     }
 
+    /// Returns the line starting at `offset`, without its `\n` or
+    /// `\r\n` terminator, borrowed straight from the mmap or sharing
+    /// the decompressed BGZF block. Unlike `read_line_at`, this
+    /// doesn't move the cursor, and only copies lines that continue
+    /// across BGZF blocks.
+    #[inline]
+    pub fn line_at(&self, offset: usize) -> Result<LineRef<'_>> {
+        let mut line = self.data.line(offset)?;
+        let len = trim_line_end(&line).len();
+        line.truncate(len);
+        Ok(line)
+    }
+
+    /// Reads the line at the cursor into the line buffer, and returns
+    /// it without its `\n` or `\r\n` terminator. Returns an empty
+    /// line at the end of the file.
     pub fn next_line(&mut self) -> Result<&[u8]> {
         self.line_buf.clear();
 
-        self.last_buf_offset = self.cursor.position() as usize;
+        self.last_buf_offset = self.position;
 
-        let n_read = self.cursor.read_until(b'\n', &mut self.line_buf)?;
+        let line = self.data.line(self.position)?;
+        self.line_buf.extend_from_slice(&line);
+        self.position += line.len();
+        let n_read = line.len();
 
-        self.current_line_len = n_read;
+        self.current_line_len = trim_line_end(&self.line_buf[..n_read]).len();
 
-        Ok(&self.line_buf[..n_read])
+        Ok(&self.line_buf[..self.current_line_len])
     }
 
     pub fn read_line_at(&mut self, offset: usize) -> Result<&[u8]> {
-        self.cursor.set_position(offset as u64);
+        self.position = offset;
         self.next_line()
     }
 
-    pub fn build_index(&mut self) -> Result<LineIndices> {
-        let start_position = self.cursor.position();
-        let current_line_len = self.current_line_len;
-        let last_buf_offset = self.last_buf_offset;
-
-        let mut segments = Vec::new();
-        let mut links = Vec::new();
-        let mut paths = Vec::new();
+    /// Indexes the lines of the file by type. The file is split into
+    /// newline-aligned chunks that are indexed in parallel, and the
+    /// results are merged in file order.
+    pub fn build_index(&self) -> Result<LineIndices> {
+        let data = &self.data;
+
+        let chunk_count = rayon::current_num_threads() * 4;
+        let chunk_size = (data.len() / chunk_count).max(INDEX_CHUNK_MIN);
+
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        while start < data.len() {
+            let end = data.next_line_start(start + chunk_size)?;
+            chunks.push((start, end));
+            start = end;
+        }
 
-        self.cursor.set_position(0);
+        let indices = chunks
+            .into_par_iter()
+            .map(|(start, end)| LineIndices::index_chunk(data, start, end))
+            .collect::<Result<Vec<_>>>()?;
 
-        let mut line_start = 0;
+        let mut res = LineIndices::default();
+        for chunk in indices {
+            res.append(chunk);
+        }
 
-        loop {
-            let line = self.next_line()?;
-            let length = line.len();
+        Ok(res)
+    }
 
-            if let Some(ref byte) = line.first() {
-                match byte {
-                    b'S' => {
-                        segments.push((line_start, length));
-                    }
-                    b'L' => {
-                        links.push(line_start);
-                    }
-                    b'P' => {
-                        paths.push(line_start);
-                    }
-                    _ => (),
-                };
+    /// Loads the line index from the `.gfai` sidecar of the input
+    /// file if there is a valid one, otherwise builds the index and
+    /// saves it to the sidecar. Failing to read or write the sidecar
+    /// isn't an error, the index is just built from scratch.
+    pub fn load_or_build_index(&self) -> Result<LineIndices> {
+        let path = match self.path.as_deref() {
+            Some(path) => path,
+            None => return self.build_index(),
+        };
+        let data_len = self.data.len();
 
-                line_start += line.len();
-            } else {
-                break;
+        match gfai::read_index(path, data_len) {
+            Ok(Some(indices)) => {
+                eprintln!("loaded line index from {}.gfai", path);
+                return Ok(indices);
             }
+            Ok(None) => (),
+            Err(err) => eprintln!("ignoring line index: {}", err),
         }
 
-        self.cursor.set_position(start_position);
-        self.current_line_len = current_line_len;
-        self.last_buf_offset = last_buf_offset;
+        let indices = self.build_index()?;
 
-        let res = LineIndices {
-            segments,
-            links,
-            paths,
-        };
+        match gfai::write_index(path, data_len, &indices) {
+            Ok(index_path) => {
+                eprintln!("saved line index to {}", index_path.display())
+            }
+            Err(err) => eprintln!("could not save line index: {}", err),
+        }
 
-        Ok(res)
+        Ok(indices)
     }
 
     pub fn current_line(&self) -> &[u8] {
@@ -149,10 +428,7 @@ impl MmapGFA {
     }
 
     pub fn current_line_name(&self) -> Option<&[u8]> {
-        let mut iter = self.line_buf.split_str("\t");
-        let _lt = iter.next()?;
-        let name = iter.next()?;
-        Some(name)
+        line_name(self.current_line())
     }
 
     pub fn parse_current_line(&self) -> Result<Line<usize, ()>> {
@@ -165,13 +441,46 @@ impl MmapGFA {
         Ok(gfa_line)
     }
 
-    #[allow(unused_variables)]
-    pub fn seek_next(&mut self, line_type: LineType) {
-        let line_sig = match line_type {
-            LineType::Segment => b'S',
-            LineType::Link => b'L',
-            LineType::Path => b'P',
+    /// Moves the cursor to the next line of the given type, starting
+    /// from the current cursor position, and reads that line so it
+    /// can be accessed with `current_line` and `parse_current_line`.
+    /// Lines that are skipped over are not copied. Returns the offset
+    /// of the line, or `None` if there are no more lines of that type.
+    pub fn seek_next(&mut self, line_type: LineType) -> Result<Option<usize>> {
+        let line_sig = line_type.line_sig();
+
+        let mut offset = self.position;
+
+        let found = loop {
+            let line = self.data.line(offset)?;
+            if line.is_empty() {
+                break None;
+            }
+
+            if line[0] == line_sig {
+                break Some(offset);
+            }
+
+            offset += line.len();
         };
-        unimplemented!();
+
+        if let Some(offset) = found {
+            self.read_line_at(offset)?;
+        } else {
+            self.position = self.data.len();
+        }
+
+        Ok(found)
+    }
+
+    /// Returns an iterator over the parsed lines of the given type,
+    /// starting from the beginning of the file. W lines can't be
+    /// parsed by the GFA1 parser, use `walks::Walk` for those.
+    pub fn typed_lines(&mut self, line_type: LineType) -> MmapGFALines<'_> {
+        self.position = 0;
+        MmapGFALines {
+            mmap_gfa: self,
+            line_type,
+        }
     }
 }
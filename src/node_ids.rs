@@ -0,0 +1,140 @@
+use handlegraph::handle::NodeId;
+
+use std::io::Write;
+
+use crate::segment_names::SegmentNameMap;
+
+/// Maps a sparse set of numeric segment IDs onto the dense range
+/// `1..=N`, preserving their order. Only the sorted original IDs are
+/// stored; the compacted ID of a segment is its rank among them.
+#[derive(Debug, Default, Clone)]
+pub struct NodeIdMap {
+    original_ids: Vec<u64>,
+}
+
+impl NodeIdMap {
+    pub fn from_ids(mut ids: Vec<u64>) -> Self {
+        ids.sort_unstable();
+        ids.dedup();
+        ids.shrink_to_fit();
+        Self { original_ids: ids }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.original_ids.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.original_ids.is_empty()
+    }
+
+    #[inline]
+    pub fn to_compact(&self, original: u64) -> Option<NodeId> {
+        let ix = self.original_ids.binary_search(&original).ok()?;
+        Some(NodeId::from(ix as u64 + 1))
+    }
+
+    #[inline]
+    pub fn to_original(&self, id: NodeId) -> Option<u64> {
+        let ix = u64::from(id).checked_sub(1)? as usize;
+        self.original_ids.get(ix).copied()
+    }
+
+    /// Writes the map as TSV, one `original<TAB>compacted` line per
+    /// node, in ID order.
+    pub fn write_tsv<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        for (ix, original) in self.original_ids.iter().enumerate() {
+            writeln!(out, "{}\t{}", original, ix + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// How the segment names in a GFA relate to the node IDs in the
+/// graph it was loaded into.
+#[derive(Debug, Clone)]
+pub enum IdMapping {
+    /// Segment names are used as node IDs directly.
+    Identity,
+    /// Node IDs are the numeric segment names plus an offset, used
+    /// for 0-based GFAs.
+    Offset(u64),
+    /// Numeric segment names were compacted to a dense range.
+    Compact(NodeIdMap),
+    /// Segment names were assigned node IDs in file order.
+    Names(SegmentNameMap),
+}
+
+impl IdMapping {
+    /// Maps a numeric segment name to its node ID. Always `None` for
+    /// `IdMapping::Names`.
+    #[inline]
+    pub fn node_id(&self, original: usize) -> Option<NodeId> {
+        let original = original as u64;
+        match self {
+            IdMapping::Identity => Some(NodeId::from(original)),
+            IdMapping::Offset(offset) => Some(NodeId::from(original + offset)),
+            IdMapping::Compact(map) => map.to_compact(original),
+            IdMapping::Names(_) => None,
+        }
+    }
+
+    /// Maps a segment name, as written in the GFA, to its node ID.
+    #[inline]
+    pub fn segment_id(&self, name: &[u8]) -> Option<NodeId> {
+        if let IdMapping::Names(names) = self {
+            names.get_id(name)
+        } else {
+            let original = std::str::from_utf8(name).ok()?.parse().ok()?;
+            self.node_id(original)
+        }
+    }
+
+    /// Returns the node ID to use for a new segment, and whether the
+    /// name was new. Only `IdMapping::Names` can detect duplicates.
+    pub fn insert_segment(&mut self, name: &[u8]) -> Option<(NodeId, bool)> {
+        if let IdMapping::Names(names) = self {
+            Some(names.get_or_insert(name))
+        } else {
+            Some((self.segment_id(name)?, true))
+        }
+    }
+
+    /// Writes the segment name of `id` as it was in the input GFA.
+    pub fn write_original<W: Write>(
+        &self,
+        out: &mut W,
+        id: NodeId,
+    ) -> std::io::Result<()> {
+        use bstr::ByteSlice;
+
+        match self {
+            IdMapping::Identity => write!(out, "{}", id),
+            IdMapping::Offset(offset) => {
+                write!(out, "{}", u64::from(id) - offset)
+            }
+            IdMapping::Compact(map) => match map.to_original(id) {
+                Some(original) => write!(out, "{}", original),
+                None => write!(out, "{}", id),
+            },
+            IdMapping::Names(names) => match names.get_name(id) {
+                Some(name) => write!(out, "{}", name.as_bstr()),
+                None => write!(out, "{}", id),
+            },
+        }
+    }
+
+    /// Writes the translation table as TSV, if the IDs were
+    /// compacted or translated from names. Returns false if there was
+    /// no table to write.
+    pub fn write_tsv<W: Write>(&self, out: &mut W) -> std::io::Result<bool> {
+        match self {
+            IdMapping::Compact(map) => map.write_tsv(out)?,
+            IdMapping::Names(names) => names.write_tsv(out)?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
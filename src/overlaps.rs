@@ -0,0 +1,264 @@
+use handlegraph::handle::Edge;
+
+use bstr::ByteSlice;
+
+use fxhash::FxHashMap;
+
+use std::fmt;
+
+/// A CIGAR operation in a GFA overlap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CigarOp {
+    Match,
+    Insertion,
+    Deletion,
+    Skip,
+    SoftClip,
+    HardClip,
+    Padding,
+    SeqMatch,
+    SeqMismatch,
+}
+
+impl CigarOp {
+    fn from_byte(op: u8) -> Option<Self> {
+        use CigarOp::*;
+        let op = match op {
+            b'M' => Match,
+            b'I' => Insertion,
+            b'D' => Deletion,
+            b'N' => Skip,
+            b'S' => SoftClip,
+            b'H' => HardClip,
+            b'P' => Padding,
+            b'=' => SeqMatch,
+            b'X' => SeqMismatch,
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    fn to_byte(self) -> u8 {
+        use CigarOp::*;
+        match self {
+            Match => b'M',
+            Insertion => b'I',
+            Deletion => b'D',
+            Skip => b'N',
+            SoftClip => b'S',
+            HardClip => b'H',
+            Padding => b'P',
+            SeqMatch => b'=',
+            SeqMismatch => b'X',
+        }
+    }
+
+    /// True if the operation consumes bases of the `from` segment,
+    /// which is the reference of the alignment.
+    fn consumes_from(self) -> bool {
+        use CigarOp::*;
+        matches!(self, Match | Deletion | Skip | SeqMatch | SeqMismatch)
+    }
+
+    /// True if the operation consumes bases of the `to` segment.
+    fn consumes_to(self) -> bool {
+        use CigarOp::*;
+        matches!(self, Match | Insertion | SoftClip | SeqMatch | SeqMismatch)
+    }
+
+    /// The operation with the roles of the two segments swapped.
+    fn swapped(self) -> Self {
+        use CigarOp::*;
+        match self {
+            Insertion => Deletion,
+            Deletion => Insertion,
+            op => op,
+        }
+    }
+}
+
+/// The overlap of a GFA link, as a CIGAR aligning the end of the
+/// link's `from` segment to the start of its `to` segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cigar {
+    ops: Vec<(u32, CigarOp)>,
+}
+
+impl Cigar {
+    /// Parses a CIGAR string. `*` and the empty string aren't
+    /// CIGARs, and return `None` along with malformed strings.
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let mut ops = Vec::new();
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let digits = rest.iter().take_while(|c| c.is_ascii_digit()).count();
+            if digits == 0 {
+                return None;
+            }
+            let len = rest[..digits].to_str().ok()?.parse().ok()?;
+            let op = CigarOp::from_byte(*rest.get(digits)?)?;
+            ops.push((len, op));
+            rest = &rest[digits + 1..];
+        }
+
+        if ops.is_empty() {
+            None
+        } else {
+            Some(Self { ops })
+        }
+    }
+
+    pub fn ops(&self) -> &[(u32, CigarOp)] {
+        &self.ops
+    }
+
+    /// Number of bases of the end of the `from` segment that overlap.
+    pub fn from_len(&self) -> usize {
+        self.ops
+            .iter()
+            .filter(|(_, op)| op.consumes_from())
+            .map(|&(len, _)| len as usize)
+            .sum()
+    }
+
+    /// Number of bases of the start of the `to` segment that overlap.
+    pub fn to_len(&self) -> usize {
+        self.ops
+            .iter()
+            .filter(|(_, op)| op.consumes_to())
+            .map(|&(len, _)| len as usize)
+            .sum()
+    }
+
+    pub fn is_blunt(&self) -> bool {
+        self.ops.iter().all(|&(len, _)| len == 0)
+    }
+
+    /// True if the overlap is an ungapped alignment, so the same
+    /// number of bases of both segments overlap.
+    pub fn is_ungapped(&self) -> bool {
+        self.ops.iter().all(|&(len, op)| {
+            len == 0
+                || matches!(
+                    op,
+                    CigarOp::Match | CigarOp::SeqMatch | CigarOp::SeqMismatch
+                )
+        })
+    }
+
+    /// The overlap of the same link traversed in the other direction,
+    /// i.e. from the reverse of `to` to the reverse of `from`.
+    pub fn reversed(&self) -> Self {
+        let ops = self
+            .ops
+            .iter()
+            .rev()
+            .map(|&(len, op)| (len, op.swapped()))
+            .collect();
+        Self { ops }
+    }
+}
+
+impl fmt::Display for Cigar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &(len, op) in self.ops.iter() {
+            write!(f, "{}{}", len, op.to_byte() as char)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the overlap field of a link between segments of lengths
+/// `from_len` and `to_len`, which are `None` if the segments have no
+/// sequence or length. Returns `None` for blunt overlaps and `*`, and
+/// an error message if the overlap isn't a CIGAR or doesn't fit on
+/// the segments.
+pub fn parse_link_overlap(
+    overlap: &[u8],
+    from_len: Option<usize>,
+    to_len: Option<usize>,
+) -> Result<Option<Cigar>, String> {
+    if overlap == b"*" {
+        return Ok(None);
+    }
+
+    let cigar = Cigar::parse(overlap)
+        .ok_or_else(|| format!("`{}` is not a CIGAR", overlap.as_bstr()))?;
+    if cigar.is_blunt() {
+        return Ok(None);
+    }
+
+    check_overlap_lengths(&cigar, from_len, to_len)?;
+    Ok(Some(cigar))
+}
+
+/// Checks that a non-blunt overlap fits on the segments it joins. A
+/// segment of unknown length can't be checked, so an overlap on it
+/// is an error too.
+pub fn check_overlap_lengths(
+    cigar: &Cigar,
+    from_len: Option<usize>,
+    to_len: Option<usize>,
+) -> Result<(), String> {
+    for (side, overlap_len, len) in [
+        ("from", cigar.from_len(), from_len),
+        ("to", cigar.to_len(), to_len),
+    ] {
+        match len {
+            Some(len) if overlap_len > len => {
+                return Err(format!(
+                    "overlap of {} bp is longer than the {} bp {} segment",
+                    overlap_len, len, side
+                ));
+            }
+            Some(_) => (),
+            None if overlap_len > 0 => {
+                return Err(format!(
+                    "overlap of {} bp on the {} segment, which has no \
+                     sequence or length",
+                    overlap_len, side
+                ));
+            }
+            None => (),
+        }
+    }
+    Ok(())
+}
+
+/// Side table holding the overlaps of the edges of a loaded graph,
+/// which a `PackedGraph` can't represent. Blunt edges aren't stored.
+#[derive(Debug, Default, Clone)]
+pub struct EdgeOverlaps {
+    overlaps: FxHashMap<Edge, Cigar>,
+}
+
+impl EdgeOverlaps {
+    pub fn insert(&mut self, edge: Edge, cigar: Cigar) {
+        self.overlaps.insert(edge, cigar);
+    }
+
+    /// Looks up the overlap of an edge in either orientation. If the
+    /// edge was stored in the other orientation, the overlap is
+    /// reversed to match.
+    pub fn get(&self, edge: Edge) -> Option<Cigar> {
+        if let Some(cigar) = self.overlaps.get(&edge) {
+            return Some(cigar.clone());
+        }
+        let Edge(left, right) = edge;
+        self.overlaps
+            .get(&Edge(right.flip(), left.flip()))
+            .map(|cigar| cigar.reversed())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Edge, &Cigar)> + '_ {
+        self.overlaps.iter().map(|(&edge, cigar)| (edge, cigar))
+    }
+
+    pub fn len(&self) -> usize {
+        self.overlaps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.overlaps.is_empty()
+    }
+}
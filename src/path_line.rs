@@ -0,0 +1,60 @@
+use gfa::gfa::Orientation;
+
+use anyhow::Result;
+
+use bstr::ByteSlice;
+
+use crate::fields::Fields;
+
+/// A GFA1 path (P) line, borrowing the line it was parsed from. Used
+/// instead of the `gfa` crate's parser when loading, since that
+/// copies the step list, and panics on steps without an orientation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathLine<'a> {
+    pub name: &'a [u8],
+    pub segment_names: &'a [u8],
+    pub overlaps: &'a [u8],
+}
+
+impl<'a> PathLine<'a> {
+    pub fn parse_line(line: &'a [u8]) -> Result<Self> {
+        let mut fields = Fields::new(line, "P")?;
+        let name = fields.next("PathName")?;
+        let segment_names = fields.next("SegmentNames")?;
+        // some tools leave out the overlaps
+        let overlaps = fields.next_optional().unwrap_or(b"*");
+
+        Ok(PathLine {
+            name,
+            segment_names,
+            overlaps,
+        })
+    }
+
+    /// Iterates through the segment names and orientations of the
+    /// path. A step that doesn't end with `+` or `-` is returned as
+    /// an error.
+    pub fn steps(&self) -> PathSteps<'a> {
+        PathSteps {
+            steps: self.segment_names.split_str(","),
+        }
+    }
+}
+
+pub struct PathSteps<'a> {
+    steps: bstr::Split<'a>,
+}
+
+impl<'a> Iterator for PathSteps<'a> {
+    type Item = std::result::Result<(&'a [u8], Orientation), &'a [u8]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let step = self.steps.next()?;
+        let orient = match step.last() {
+            Some(b'+') => Orientation::Forward,
+            Some(b'-') => Orientation::Backward,
+            _ => return Some(Err(step)),
+        };
+        Some(Ok((&step[..step.len() - 1], orient)))
+    }
+}
@@ -0,0 +1,145 @@
+use handlegraph::pathhandlegraph::*;
+
+use handlegraph::packedgraph::PackedGraph;
+
+use anyhow::{bail, Result};
+
+use bstr::ByteSlice;
+
+use fxhash::FxHashSet;
+
+use regex::bytes::Regex;
+
+use structopt::StructOpt;
+
+use std::fmt;
+
+/// Which embedded paths to operate on, matched against path names.
+#[derive(Debug, Clone)]
+pub enum PathSelection {
+    Prefix(Vec<u8>),
+    Regex(Regex),
+    Names(FxHashSet<Vec<u8>>),
+    All,
+}
+
+impl PathSelection {
+    /// Reads a file with one path name per line. Empty lines are
+    /// ignored.
+    pub fn from_names_file(file_path: &str) -> Result<Self> {
+        let contents = std::fs::read(file_path)?;
+        let names = contents
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| line.to_vec())
+            .collect::<FxHashSet<_>>();
+
+        if names.is_empty() {
+            bail!("Path name file {} contains no names", file_path);
+        }
+
+        Ok(PathSelection::Names(names))
+    }
+
+    pub fn matches(&self, name: &[u8]) -> bool {
+        match self {
+            PathSelection::Prefix(prefix) => name.starts_with(prefix),
+            PathSelection::Regex(regex) => regex.is_match(name),
+            PathSelection::Names(names) => names.contains(name),
+            PathSelection::All => true,
+        }
+    }
+
+    /// Returns the names of all paths in `graph` that match the
+    /// selection, in path ID order. Fails if no path matches.
+    pub fn select_path_names(
+        &self,
+        graph: &PackedGraph,
+    ) -> Result<Vec<Vec<u8>>> {
+        let mut selected = Vec::new();
+
+        for path_id in graph.path_ids() {
+            if let Some(name) = graph.get_path_name_vec(path_id) {
+                if self.matches(&name) {
+                    selected.push(name);
+                }
+            }
+        }
+
+        if selected.is_empty() {
+            bail!(
+                "No paths matched {} (the graph has {} paths)",
+                self,
+                graph.path_count()
+            );
+        }
+
+        if let PathSelection::Names(names) = self {
+            let found = selected.iter().collect::<FxHashSet<_>>();
+            for name in names.iter().filter(|name| !found.contains(name)) {
+                eprintln!("path {} not found in graph", name.as_bstr());
+            }
+        }
+
+        Ok(selected)
+    }
+}
+
+impl fmt::Display for PathSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathSelection::Prefix(prefix) => {
+                write!(f, "prefix `{}`", prefix.as_bstr())
+            }
+            PathSelection::Regex(regex) => write!(f, "regex `{}`", regex),
+            PathSelection::Names(names) => {
+                write!(f, "any of {} path names", names.len())
+            }
+            PathSelection::All => write!(f, "all paths"),
+        }
+    }
+}
+
+/// Command line options for building a `PathSelection`. At most one
+/// of the options can be given.
+#[derive(StructOpt, Debug)]
+pub struct PathSelectionArgs {
+    /// Select paths whose names start with this prefix
+    #[structopt(
+        long,
+        conflicts_with_all = &["path-regex", "path-names", "all-paths"]
+    )]
+    pub path_prefix: Option<String>,
+
+    /// Select paths whose names match this regex
+    #[structopt(long, conflicts_with_all = &["path-names", "all-paths"])]
+    pub path_regex: Option<String>,
+
+    /// Select the paths named in this file, one name per line
+    #[structopt(long, conflicts_with = "all-paths")]
+    pub path_names: Option<String>,
+
+    /// Select every path in the graph
+    #[structopt(long)]
+    pub all_paths: bool,
+}
+
+impl PathSelectionArgs {
+    /// Builds the selection, using `default_prefix` if no option was
+    /// provided.
+    pub fn selection(&self, default_prefix: &str) -> Result<PathSelection> {
+        let selection = if let Some(prefix) = &self.path_prefix {
+            PathSelection::Prefix(prefix.as_bytes().to_vec())
+        } else if let Some(regex) = &self.path_regex {
+            PathSelection::Regex(Regex::new(regex)?)
+        } else if let Some(file_path) = &self.path_names {
+            PathSelection::from_names_file(file_path)?
+        } else if self.all_paths {
+            PathSelection::All
+        } else {
+            PathSelection::Prefix(default_prefix.as_bytes().to_vec())
+        };
+        Ok(selection)
+    }
+}
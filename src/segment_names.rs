@@ -0,0 +1,71 @@
+use handlegraph::handle::NodeId;
+
+use bstr::ByteSlice;
+
+use fxhash::FxHashMap;
+
+use std::io::Write;
+
+/// Translation table between GFA segment names and the dense node
+/// IDs they are assigned when loaded into a graph. IDs are assigned
+/// in insertion order, starting from 1.
+#[derive(Debug, Default, Clone)]
+pub struct SegmentNameMap {
+    name_to_id: FxHashMap<Vec<u8>, NodeId>,
+    id_to_name: Vec<Vec<u8>>,
+}
+
+impl SegmentNameMap {
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut name_to_id = FxHashMap::default();
+        name_to_id.reserve(capacity);
+        let id_to_name = Vec::with_capacity(capacity);
+        Self {
+            name_to_id,
+            id_to_name,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.id_to_name.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.id_to_name.is_empty()
+    }
+
+    /// Returns the ID of `name`, and whether the name was inserted
+    /// by this call.
+    pub fn get_or_insert(&mut self, name: &[u8]) -> (NodeId, bool) {
+        if let Some(&id) = self.name_to_id.get(name) {
+            return (id, false);
+        }
+
+        self.id_to_name.push(name.to_vec());
+        let id = NodeId::from(self.id_to_name.len() as u64);
+        self.name_to_id.insert(name.to_vec(), id);
+        (id, true)
+    }
+
+    #[inline]
+    pub fn get_id(&self, name: &[u8]) -> Option<NodeId> {
+        self.name_to_id.get(name).copied()
+    }
+
+    #[inline]
+    pub fn get_name(&self, id: NodeId) -> Option<&[u8]> {
+        let ix = u64::from(id).checked_sub(1)? as usize;
+        self.id_to_name.get(ix).map(|name| name.as_slice())
+    }
+
+    /// Writes the table as TSV, one `name<TAB>id` line per segment,
+    /// in ID order.
+    pub fn write_tsv<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        for (ix, name) in self.id_to_name.iter().enumerate() {
+            writeln!(out, "{}\t{}", name.as_bstr(), ix + 1)?;
+        }
+        Ok(())
+    }
+}
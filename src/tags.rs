@@ -0,0 +1,118 @@
+use handlegraph::{
+    handle::{Edge, NodeId},
+    pathhandlegraph::PathId,
+};
+
+use bstr::ByteSlice;
+
+use fxhash::FxHashMap;
+
+/// Number of required fields, including the line type, of each line
+/// type that can have optional fields.
+pub const SEGMENT_FIELDS: usize = 3;
+pub const LINK_FIELDS: usize = 6;
+pub const JUMP_FIELDS: usize = 6;
+pub const CONTAINMENT_FIELDS: usize = 7;
+pub const PATH_FIELDS: usize = 4;
+pub const WALK_FIELDS: usize = 7;
+pub const GFA2_SEGMENT_FIELDS: usize = 4;
+pub const GFA2_EDGE_FIELDS: usize = 9;
+pub const GFA2_GROUP_FIELDS: usize = 3;
+
+/// Returns the optional fields of a GFA line, i.e. everything after
+/// the first `required` tab-separated fields, without the trailing
+/// newline. Returns `None` if the line has no optional fields.
+pub fn optional_fields(line: &[u8], required: usize) -> Option<&[u8]> {
+    let line = line.trim_end_with(|c| c == '\n' || c == '\r');
+    let mut start = 0;
+    for _ in 0..required {
+        let ix = line[start..].find_byte(b'\t')?;
+        start += ix + 1;
+    }
+    let fields = &line[start..];
+    if fields.is_empty() {
+        None
+    } else {
+        Some(fields)
+    }
+}
+
+/// Returns the value of the `LN:i` tag of a line with `required`
+/// required fields, which GFA1 uses for the length of segments that
+/// have no sequence.
+pub fn length_tag(line: &[u8], required: usize) -> Option<usize> {
+    optional_fields(line, required)?
+        .split_str("\t")
+        .find_map(|field| field.strip_prefix(b"LN:i:"))
+        .and_then(|value| value.to_str().ok()?.parse().ok())
+}
+
+/// Side tables holding the optional fields (tags) of the segments,
+/// links, and paths of a loaded graph. The fields are stored as the
+/// raw tab-separated text from the input, so they can be written back
+/// out unchanged.
+#[derive(Debug, Default, Clone)]
+pub struct GraphTags {
+    pub segments: FxHashMap<NodeId, Vec<u8>>,
+    pub links: FxHashMap<Edge, Vec<u8>>,
+    pub paths: FxHashMap<PathId, Vec<u8>>,
+}
+
+impl GraphTags {
+    pub fn add_segment(&mut self, id: NodeId, line: &[u8], required: usize) {
+        if let Some(fields) = optional_fields(line, required) {
+            self.segments.insert(id, fields.to_vec());
+        }
+    }
+
+    pub fn add_link(&mut self, edge: Edge, line: &[u8], required: usize) {
+        if let Some(fields) = optional_fields(line, required) {
+            self.links.insert(edge, fields.to_vec());
+        }
+    }
+
+    pub fn add_path(&mut self, path: PathId, line: &[u8], required: usize) {
+        if let Some(fields) = optional_fields(line, required) {
+            self.paths.insert(path, fields.to_vec());
+        }
+    }
+
+    pub fn segment(&self, id: NodeId) -> Option<&[u8]> {
+        self.segments.get(&id).map(|tags| tags.as_slice())
+    }
+
+    /// Removes the `LN:i` tag of a segment, e.g. after its sequence has
+    /// been trimmed, so that it's written with its new length.
+    pub fn remove_length_tag(&mut self, id: NodeId) {
+        if let Some(fields) = self.segments.get_mut(&id) {
+            let kept = fields
+                .split_str("\t")
+                .filter(|field| !field.starts_with(b"LN:i:"))
+                .collect::<Vec<_>>();
+            if kept.is_empty() {
+                self.segments.remove(&id);
+            } else {
+                *fields = kept.join(&b"\t"[..]);
+            }
+        }
+    }
+
+    /// Looks up the tags of an edge in either orientation.
+    pub fn link(&self, edge: Edge) -> Option<&[u8]> {
+        let Edge(left, right) = edge;
+        self.links
+            .get(&edge)
+            .or_else(|| self.links.get(&Edge(right.flip(), left.flip())))
+            .map(|tags| tags.as_slice())
+    }
+
+    pub fn path(&self, path: PathId) -> Option<&[u8]> {
+        self.paths.get(&path).map(|tags| tags.as_slice())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+            && self.links.is_empty()
+            && self.paths.is_empty()
+    }
+}
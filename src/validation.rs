@@ -0,0 +1,588 @@
+use handlegraph::handle::{Edge, Handle, NodeId};
+
+use gfa::gfa::Orientation;
+
+use anyhow::Result;
+
+use bstr::ByteSlice;
+
+use fxhash::{FxHashMap, FxHashSet};
+
+use rayon::prelude::*;
+
+use crate::{
+    containments::ContainmentLine,
+    gfa2,
+    jumps::Jump,
+    load_error::{set_line_numbers, LoadError, LoadErrorKind},
+    mmap_gfa::{LineIndices, LineType, MmapGFA},
+    overlaps,
+    path_line::PathLine,
+    tags,
+    walks::Walk,
+};
+
+/// Counts of the records that were checked.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ValidationCounts {
+    pub segments: usize,
+    pub links: usize,
+    pub paths: usize,
+}
+
+/// The result of validating a GFA. The errors are sorted by offset.
+#[derive(Debug, Default, Clone)]
+pub struct Validation {
+    pub counts: ValidationCounts,
+    pub errors: Vec<LoadError>,
+}
+
+impl Validation {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// True if `c` is an IUPAC nucleotide code.
+fn is_iupac(c: u8) -> bool {
+    matches!(
+        c.to_ascii_uppercase(),
+        b'A' | b'C'
+            | b'G'
+            | b'T'
+            | b'U'
+            | b'R'
+            | b'Y'
+            | b'S'
+            | b'W'
+            | b'K'
+            | b'M'
+            | b'B'
+            | b'D'
+            | b'H'
+            | b'V'
+            | b'N'
+    )
+}
+
+fn parse_orient(field: &[u8]) -> Option<Orientation> {
+    match field {
+        b"+" => Some(Orientation::Forward),
+        b"-" => Some(Orientation::Backward),
+        _ => None,
+    }
+}
+
+fn fmt_step(name: &[u8], orient: Orientation) -> String {
+    let orient = if orient.is_reverse() { '-' } else { '+' };
+    format!("{}{}", name.as_bstr(), orient)
+}
+
+/// The segments and links of a GFA, used to check the paths.
+struct Topology {
+    segments: FxHashMap<Vec<u8>, NodeId>,
+    /// Segment lengths, indexed by ID - 1. Unknown for segments
+    /// without a sequence or LN tag.
+    lengths: Vec<Option<usize>>,
+    /// Edges are stored in their canonical orientation.
+    edges: FxHashSet<Edge>,
+    /// GFA2 edge names, which O lines can refer to.
+    edge_names: FxHashSet<Vec<u8>>,
+}
+
+impl Topology {
+    fn handle(&self, name: &[u8], orient: Orientation) -> Option<Handle> {
+        let id = *self.segments.get(name)?;
+        Some(Handle::new(id, orient))
+    }
+
+    fn length(&self, handle: Option<Handle>) -> Option<usize> {
+        let ix = u64::from(handle?.id()) as usize - 1;
+        *self.lengths.get(ix)?
+    }
+
+    fn has_edge(&self, from: Handle, to: Handle) -> bool {
+        self.edges.contains(&Edge::edge_handle(from, to))
+    }
+}
+
+/// Checks the steps of a path: that they're all well-formed, that
+/// all the segments exist, and that consecutive steps are linked.
+/// Only the first problem of each kind is reported for each path.
+fn check_steps<'a, I>(
+    topology: &Topology,
+    steps: I,
+    line: &[u8],
+    line_type: LineType,
+    offset: usize,
+    errors: &mut Vec<LoadError>,
+) where
+    I: Iterator<Item = std::result::Result<(&'a [u8], Orientation), &'a [u8]>>,
+{
+    let error = |kind| LoadError::at_line(kind, line_type, line, offset);
+
+    let mut bad_step = None;
+    let mut missing_segment = None;
+    let mut missing_links = 0;
+    let mut first_missing_link = None;
+
+    let mut prev: Option<(Handle, &[u8], Orientation)> = None;
+
+    for step in steps {
+        let (name, orient) = match step {
+            Ok(step) => step,
+            Err(step) => {
+                bad_step.get_or_insert(step);
+                prev = None;
+                continue;
+            }
+        };
+
+        let handle = match topology.handle(name, orient) {
+            Some(handle) => handle,
+            None => {
+                missing_segment.get_or_insert(name);
+                prev = None;
+                continue;
+            }
+        };
+
+        if let Some((prev_handle, prev_name, prev_orient)) = prev {
+            if !topology.has_edge(prev_handle, handle) {
+                missing_links += 1;
+                first_missing_link.get_or_insert_with(|| {
+                    format!(
+                        "{},{}",
+                        fmt_step(prev_name, prev_orient),
+                        fmt_step(name, orient)
+                    )
+                });
+            }
+        }
+
+        prev = Some((handle, name, orient));
+    }
+
+    if let Some(step) = bad_step {
+        let kind = LoadErrorKind::Parse("step has no orientation".to_string());
+        errors.push(error(kind).with_field("steps", step));
+    }
+    if let Some(name) = missing_segment {
+        let kind = LoadErrorKind::MissingSegment;
+        errors.push(error(kind).with_field("steps", name));
+    }
+    if let Some(pair) = first_missing_link {
+        let kind = LoadErrorKind::MissingLink(missing_links);
+        errors.push(error(kind).with_field("steps", pair.as_bytes()));
+    }
+}
+
+/// Validates a GFA without building a graph. The segments and links
+/// are indexed first, then the paths are checked in parallel.
+pub fn validate_gfa(
+    mmap_gfa: &MmapGFA,
+    indices: &LineIndices,
+) -> Result<Validation> {
+    let mut errors = Vec::new();
+
+    let mut topology = Topology {
+        segments: FxHashMap::default(),
+        lengths: Vec::with_capacity(indices.segments.len()),
+        edges: FxHashSet::default(),
+        edge_names: FxHashSet::default(),
+    };
+    topology.segments.reserve(indices.segments.len());
+
+    for &(offset, _) in indices.segments.iter() {
+        let line = mmap_gfa.line_at(offset)?;
+        let line = &*line;
+        let error =
+            |kind| LoadError::at_line(kind, LineType::Segment, line, offset);
+
+        let (name, sequence, length) = if indices.gfa2 {
+            match gfa2::Segment::parse_line(line) {
+                Ok(segment) => {
+                    (segment.name, segment.sequence, Some(segment.length))
+                }
+                Err(err) => {
+                    let kind = LoadErrorKind::Parse(err.to_string());
+                    errors.push(error(kind));
+                    continue;
+                }
+            }
+        } else {
+            let mut fields = line.trim_end().split_str("\t").skip(1);
+            match (fields.next(), fields.next()) {
+                (Some(name), Some(sequence)) => {
+                    let length = if sequence == b"*" {
+                        tags::length_tag(line, tags::SEGMENT_FIELDS)
+                    } else {
+                        Some(sequence.len())
+                    };
+                    (name, sequence, length)
+                }
+                _ => {
+                    let kind = LoadErrorKind::Parse(
+                        "S line is missing required fields".to_string(),
+                    );
+                    errors.push(error(kind));
+                    continue;
+                }
+            }
+        };
+
+        let id = NodeId::from(topology.lengths.len() as u64 + 1);
+        if topology.segments.contains_key(name) {
+            let kind = LoadErrorKind::DuplicateSegment;
+            errors.push(error(kind).with_field("name", name));
+        } else {
+            topology.segments.insert(name.to_vec(), id);
+            topology.lengths.push(length);
+        }
+
+        if sequence != b"*" {
+            if let Some(pos) = sequence.iter().position(|&c| !is_iupac(c)) {
+                let value =
+                    format!("{} at position {}", sequence[pos] as char, pos);
+                let kind = LoadErrorKind::InvalidSequence;
+                errors
+                    .push(error(kind).with_field("sequence", value.as_bytes()));
+            }
+        }
+    }
+
+    for &offset in indices.links.iter() {
+        let line = mmap_gfa.line_at(offset)?;
+        let line = &*line;
+        let error =
+            |kind| LoadError::at_line(kind, LineType::Link, line, offset);
+
+        let fields = line.trim_end().split_str("\t").collect::<Vec<_>>();
+        if fields.len() < 6 {
+            let kind = LoadErrorKind::Parse(
+                "L line is missing required fields".to_string(),
+            );
+            errors.push(error(kind));
+            continue;
+        }
+
+        let from_orient = parse_orient(fields[2]);
+        let to_orient = parse_orient(fields[4]);
+        let (from_orient, to_orient) = match (from_orient, to_orient) {
+            (Some(from), Some(to)) => (from, to),
+            (None, _) => {
+                let kind = LoadErrorKind::Parse("invalid orientation".into());
+                errors.push(error(kind).with_field("FromOrient", fields[2]));
+                continue;
+            }
+            (_, None) => {
+                let kind = LoadErrorKind::Parse("invalid orientation".into());
+                errors.push(error(kind).with_field("ToOrient", fields[4]));
+                continue;
+            }
+        };
+
+        let from = topology.handle(fields[1], from_orient);
+        let to = topology.handle(fields[3], to_orient);
+
+        match (from, to) {
+            (Some(from), Some(to)) => {
+                let from_len = topology.length(Some(from));
+                let to_len = topology.length(Some(to));
+                let kind = match overlaps::parse_link_overlap(
+                    fields[5], from_len, to_len,
+                ) {
+                    Ok(None) => None,
+                    Ok(Some(cigar)) => {
+                        Some(LoadErrorKind::NonBluntOverlap(cigar.to_string()))
+                    }
+                    Err(reason) => Some(LoadErrorKind::InvalidOverlap(reason)),
+                };
+                if let Some(kind) = kind {
+                    errors.push(error(kind).with_field("Overlap", fields[5]));
+                }
+                topology.edges.insert(Edge::edge_handle(from, to));
+            }
+            (None, _) => {
+                let kind = LoadErrorKind::MissingSegment;
+                errors.push(error(kind).with_field("From", fields[1]));
+            }
+            (_, None) => {
+                let kind = LoadErrorKind::MissingSegment;
+                errors.push(error(kind).with_field("To", fields[3]));
+            }
+        }
+    }
+
+    // paths can step across jumps as well as links
+    for &offset in indices.jumps.iter() {
+        let line = mmap_gfa.line_at(offset)?;
+        let line = &*line;
+        let error =
+            |kind| LoadError::at_line(kind, LineType::Jump, line, offset);
+
+        let jump = match Jump::parse_line(line) {
+            Ok(jump) => jump,
+            Err(err) => {
+                errors.push(error(LoadErrorKind::Parse(err.to_string())));
+                continue;
+            }
+        };
+
+        let from = topology.handle(jump.from_segment, jump.from_orient);
+        let to = topology.handle(jump.to_segment, jump.to_orient);
+        match (from, to) {
+            (Some(from), Some(to)) => {
+                topology.edges.insert(Edge::edge_handle(from, to));
+            }
+            (None, _) => {
+                let kind = LoadErrorKind::MissingSegment;
+                errors.push(error(kind).with_field("From", jump.from_segment));
+            }
+            (_, None) => {
+                let kind = LoadErrorKind::MissingSegment;
+                errors.push(error(kind).with_field("To", jump.to_segment));
+            }
+        }
+    }
+
+    for &offset in indices.containments.iter() {
+        let line = mmap_gfa.line_at(offset)?;
+        let line = &*line;
+        let error = |kind| {
+            LoadError::at_line(kind, LineType::Containment, line, offset)
+        };
+
+        let containment = match ContainmentLine::parse_line(line) {
+            Ok(containment) => containment,
+            Err(err) => {
+                errors.push(error(LoadErrorKind::Parse(err.to_string())));
+                continue;
+            }
+        };
+
+        let container = topology
+            .handle(containment.container, containment.container_orient);
+        let contained = topology
+            .handle(containment.contained, containment.contained_orient);
+        match (container, contained) {
+            (Some(_), Some(_)) => (),
+            (None, _) => {
+                let kind = LoadErrorKind::MissingSegment;
+                let name = containment.container;
+                errors.push(error(kind).with_field("Container", name));
+                continue;
+            }
+            (_, None) => {
+                let kind = LoadErrorKind::MissingSegment;
+                let name = containment.contained;
+                errors.push(error(kind).with_field("Contained", name));
+                continue;
+            }
+        }
+
+        let pos = containment.pos;
+        let container_len = topology.length(container);
+        let overlap = match container_len {
+            Some(len) if pos > len => Err(format!(
+                "position {} is past the end of the {} bp container",
+                pos, len
+            )),
+            _ => overlaps::parse_link_overlap(
+                containment.overlap,
+                container_len.map(|len| len - pos),
+                topology.length(contained),
+            ),
+        };
+        if let Err(reason) = overlap {
+            let kind = LoadErrorKind::InvalidOverlap(reason);
+            errors.push(error(kind).with_field("Overlap", containment.overlap));
+        }
+    }
+
+    for &offset in indices.edges.iter() {
+        let line = mmap_gfa.line_at(offset)?;
+        let line = &*line;
+        let error =
+            |kind| LoadError::at_line(kind, LineType::Edge, line, offset);
+
+        let edge = match gfa2::Edge::parse_line(line) {
+            Ok(edge) => edge,
+            Err(err) => {
+                errors.push(error(LoadErrorKind::Parse(err.to_string())));
+                continue;
+            }
+        };
+        topology.edge_names.insert(edge.name.to_vec());
+
+        let id1 = topology.segments.get(edge.sid1.0).copied();
+        let id2 = topology.segments.get(edge.sid2.0).copied();
+        let (id1, id2) = match (id1, id2) {
+            (Some(id1), Some(id2)) => (id1, id2),
+            (None, _) => {
+                let kind = LoadErrorKind::MissingSegment;
+                errors.push(error(kind).with_field("sid1", edge.sid1.0));
+                continue;
+            }
+            (_, None) => {
+                let kind = LoadErrorKind::MissingSegment;
+                errors.push(error(kind).with_field("sid2", edge.sid2.0));
+                continue;
+            }
+        };
+
+        // containments and other alignments don't connect segments
+        if let Some(sides) = edge.dovetail_sides() {
+            // overlaps are reported like those of links, and ones
+            // that can't be written as a CIGAR can't be loaded
+            if !edge.is_blunt() {
+                let kind = match edge.match_overlap() {
+                    Some(cigar) => {
+                        LoadErrorKind::NonBluntOverlap(cigar.to_string())
+                    }
+                    None => {
+                        let (len1, len2) = edge.interval_lens();
+                        LoadErrorKind::InvalidOverlap(format!(
+                            "dovetail overlap intervals of {} and {} bp",
+                            len1, len2
+                        ))
+                    }
+                };
+                errors.push(error(kind).with_field("eid", edge.name));
+            }
+            let Edge(left, right) = gfa2::dovetail_edge(sides, id1, id2);
+            topology.edges.insert(Edge::edge_handle(left, right));
+        }
+    }
+
+    let mut path_names: FxHashSet<Vec<u8>> = FxHashSet::default();
+
+    let path_lines = indices
+        .paths
+        .iter()
+        .map(|&offset| (offset, LineType::Path))
+        .chain(indices.walks.iter().map(|&offset| (offset, LineType::Walk)))
+        .chain(
+            indices
+                .ordered_groups
+                .iter()
+                .map(|&offset| (offset, LineType::OGroup)),
+        )
+        .collect::<Vec<_>>();
+
+    for &(offset, line_type) in path_lines.iter() {
+        let line = mmap_gfa.line_at(offset)?;
+        let line = &*line;
+        let name = match line_type {
+            LineType::Walk => {
+                Walk::parse_line(line).map(|walk| walk.path_name())
+            }
+            LineType::OGroup => gfa2::Group::parse_ordered(line)
+                .map(|group| group.name.to_vec()),
+            _ => PathLine::parse_line(line).map(|path| path.name.to_vec()),
+        };
+        // parse errors are reported by the path checks
+        if let Ok(name) = name {
+            if path_names.contains(&name) {
+                let kind = LoadErrorKind::DuplicatePath;
+                let error = LoadError::at_line(kind, line_type, line, offset);
+                errors.push(error.with_field("name", &name));
+            } else {
+                path_names.insert(name);
+            }
+        }
+    }
+
+    let topology = &topology;
+
+    let path_errors = path_lines
+        .par_iter()
+        .map(|&(offset, line_type)| {
+            let line = mmap_gfa.line_at(offset)?;
+            let line = &*line;
+            let mut errors = Vec::new();
+            let error = |err: anyhow::Error| {
+                let kind = LoadErrorKind::Parse(err.to_string());
+                LoadError::at_line(kind, line_type, line, offset)
+            };
+
+            match line_type {
+                LineType::Walk => match Walk::parse_line(line) {
+                    Ok(walk) => check_steps(
+                        topology,
+                        walk.steps(),
+                        line,
+                        line_type,
+                        offset,
+                        &mut errors,
+                    ),
+                    Err(err) => errors.push(error(err)),
+                },
+                LineType::OGroup => match gfa2::Group::parse_ordered(line) {
+                    Ok(group) => {
+                        let mut bad_step = None;
+                        let mut missing = None;
+                        for reference in group.ordered_references() {
+                            match reference {
+                                Ok((name, _)) => {
+                                    if !topology.segments.contains_key(name)
+                                        && !topology.edge_names.contains(name)
+                                    {
+                                        missing.get_or_insert(name);
+                                    }
+                                }
+                                Err(reference) => {
+                                    bad_step.get_or_insert(reference);
+                                }
+                            }
+                        }
+                        let error = |kind| {
+                            LoadError::at_line(kind, line_type, line, offset)
+                        };
+                        if let Some(reference) = bad_step {
+                            let kind = LoadErrorKind::Parse(
+                                "step has no orientation".to_string(),
+                            );
+                            errors.push(
+                                error(kind).with_field("references", reference),
+                            );
+                        }
+                        if let Some(name) = missing {
+                            let kind = LoadErrorKind::MissingSegment;
+                            errors.push(
+                                error(kind).with_field("references", name),
+                            );
+                        }
+                    }
+                    Err(err) => errors.push(error(err)),
+                },
+                _ => match PathLine::parse_line(line) {
+                    Ok(path) => check_steps(
+                        topology,
+                        path.steps(),
+                        line,
+                        line_type,
+                        offset,
+                        &mut errors,
+                    ),
+                    Err(err) => errors.push(error(err)),
+                },
+            }
+
+            Ok(errors)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    errors.extend(path_errors.into_iter().flatten());
+
+    set_line_numbers(&mut errors, mmap_gfa.data())?;
+
+    Ok(Validation {
+        counts: ValidationCounts {
+            segments: indices.segments.len(),
+            links: indices.links.len() + indices.edges.len(),
+            paths: path_lines.len(),
+        },
+        errors,
+    })
+}
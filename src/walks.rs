@@ -0,0 +1,145 @@
+use gfa::gfa::Orientation;
+
+use anyhow::{anyhow, Result};
+
+use bstr::ByteSlice;
+
+use std::io::Write;
+
+use crate::fields::{parse_usize, Fields};
+
+/// A GFA 1.1 walk (W) line, borrowing the line it was parsed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Walk<'a> {
+    pub sample: &'a [u8],
+    pub hap_index: usize,
+    pub seq_id: &'a [u8],
+    pub seq_range: Option<(usize, usize)>,
+    pub walk: &'a [u8],
+}
+
+fn parse_coord(field: &[u8]) -> Result<Option<usize>> {
+    if field == b"*" {
+        return Ok(None);
+    }
+    let coord = field
+        .to_str()
+        .ok()
+        .and_then(|field| field.parse::<usize>().ok())
+        .ok_or_else(|| anyhow!("invalid coordinate `{}`", field.as_bstr()))?;
+    Ok(Some(coord))
+}
+
+impl<'a> Walk<'a> {
+    pub fn parse_line(line: &'a [u8]) -> Result<Self> {
+        let mut fields = Fields::new(line, "W")?;
+        let sample = fields.next("SampleId")?;
+        let hap_index = parse_usize(fields.next("HapIndex")?, "HapIndex")?;
+        let seq_id = fields.next("SeqId")?;
+        let seq_start = parse_coord(fields.next("SeqStart")?)?;
+        let seq_end = parse_coord(fields.next("SeqEnd")?)?;
+        let walk = fields.next("Walk")?;
+
+        let seq_range = match (seq_start, seq_end) {
+            (Some(start), Some(end)) => Some((start, end)),
+            _ => None,
+        };
+
+        Ok(Walk {
+            sample,
+            hap_index,
+            seq_id,
+            seq_range,
+            walk,
+        })
+    }
+
+    /// The PanSN name of the embedded path created from this walk,
+    /// `sample#hap_index#seq_id`, followed by `:start-end` if the
+    /// walk has a sequence range.
+    pub fn path_name(&self) -> Vec<u8> {
+        let mut name =
+            Vec::with_capacity(self.sample.len() + self.seq_id.len() + 32);
+        name.extend_from_slice(self.sample);
+        write!(name, "#{}#", self.hap_index).unwrap();
+        name.extend_from_slice(self.seq_id);
+        if let Some((start, end)) = self.seq_range {
+            write!(name, ":{}-{}", start, end).unwrap();
+        }
+        name
+    }
+
+    /// Iterates through the segment names and orientations of the
+    /// walk. A step that doesn't start with `>` or `<` is returned as
+    /// an error.
+    pub fn steps(&self) -> WalkSteps<'a> {
+        WalkSteps { walk: self.walk }
+    }
+}
+
+pub struct WalkSteps<'a> {
+    walk: &'a [u8],
+}
+
+impl<'a> Iterator for WalkSteps<'a> {
+    type Item = std::result::Result<(&'a [u8], Orientation), &'a [u8]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = *self.walk.first()?;
+        let end = self.walk[1..]
+            .find_byteset(b"><")
+            .map_or(self.walk.len(), |end| end + 1);
+        let step = &self.walk[..end];
+        self.walk = &self.walk[end..];
+
+        let orient = match first {
+            b'>' => Orientation::Forward,
+            b'<' => Orientation::Backward,
+            _ => return Some(Err(step)),
+        };
+        Some(Ok((&step[1..], orient)))
+    }
+}
+
+/// The parts of a PanSN path name, as produced by `Walk::path_name`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkName<'a> {
+    pub sample: &'a [u8],
+    pub hap_index: usize,
+    pub seq_id: &'a [u8],
+    pub seq_range: Option<(usize, usize)>,
+}
+
+impl<'a> WalkName<'a> {
+    /// Parses a path name of the form `sample#hap_index#seq_id`,
+    /// optionally followed by `:start-end`.
+    pub fn parse(name: &'a [u8]) -> Option<Self> {
+        let mut parts = name.splitn_str(3, "#");
+        let sample = parts.next()?;
+        let hap_index = parts.next()?.to_str().ok()?.parse::<usize>().ok()?;
+        let mut seq_id = parts.next()?;
+
+        if sample.is_empty() || seq_id.is_empty() {
+            return None;
+        }
+
+        let mut seq_range = None;
+        if let Some(ix) = seq_id.rfind_byte(b':') {
+            let range = seq_id[ix + 1..].to_str().ok()?;
+            let mut coords = range.splitn(2, '-');
+            let start = coords.next().and_then(|c| c.parse::<usize>().ok());
+            let end = coords.next().and_then(|c| c.parse::<usize>().ok());
+            if let (Some(start), Some(end)) = (start, end) {
+                seq_range = Some((start, end));
+                seq_id = &seq_id[..ix];
+            }
+        }
+
+        Some(WalkName {
+            sample,
+            hap_index,
+            seq_id,
+            seq_range,
+        })
+    }
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use handlegraph::{handle::Edge, handlegraph::*, pathhandlegraph::*};
+
+use handlegraph_cli::{
+    bluntify::bluntify,
+    io::LoadedGraph,
+    load_error::{LoadError, LoadErrorKind},
+    overlaps::{check_overlap_lengths, Cigar},
+};
+
+use std::collections::BTreeMap;
+
+use common::{fixture, load, GraphSummary};
+
+/// The sequence each path spells, with the overlapping bases of each
+/// step after the first left out.
+fn spelled_paths(loaded: &LoadedGraph) -> BTreeMap<Vec<u8>, Vec<u8>> {
+    let graph = &loaded.graph;
+    graph
+        .path_ids()
+        .map(|path_id| {
+            let mut sequence = Vec::new();
+            let mut prev = None;
+            for step in graph.path_steps(path_id).unwrap() {
+                let handle = step.handle();
+                let overlap = prev
+                    .and_then(|prev| loaded.overlaps.get(Edge(prev, handle)))
+                    .map(|cigar| cigar.to_len())
+                    .unwrap_or(0);
+                sequence.extend(&graph.sequence_vec(handle)[overlap..]);
+                prev = Some(handle);
+            }
+            (graph.get_path_name_vec(path_id).unwrap(), sequence)
+        })
+        .collect()
+}
+
+#[test]
+fn bluntify_keeps_path_sequences() {
+    let loaded = load("bluntify.gfa", false).unwrap();
+    let paths = spelled_paths(&loaded);
+    assert_eq!(paths[&b"p1"[..]], b"ACGTACGGATTT");
+
+    let blunt = bluntify(loaded).unwrap();
+
+    assert!(blunt.overlaps.is_empty());
+    assert_eq!(spelled_paths(&blunt), paths);
+}
+
+#[test]
+fn bluntify_drops_length_tags_of_trimmed_nodes() {
+    let blunt = bluntify(load("bluntify.gfa", false).unwrap()).unwrap();
+    let summary = GraphSummary::new(&blunt);
+
+    let segment = |name: &str| {
+        let (sequence, tags) = &summary.segments[name];
+        (sequence.as_str(), tags.as_str())
+    };
+    assert_eq!(segment("s1"), ("ACGTAC", ""));
+    assert_eq!(segment("s2"), ("GGA", "RC:i:4"));
+    assert_eq!(segment("s3"), ("TTT", ""));
+}
+
+#[test]
+fn overlap_longer_than_segment_is_rejected() {
+    let err = match load("overlap_too_long.gfa", false) {
+        Ok(_) => panic!("loaded {}", fixture("overlap_too_long.gfa")),
+        Err(err) => err,
+    };
+    let err = err.downcast_ref::<LoadError>().unwrap();
+
+    assert_eq!(err.kind.code(), "invalid_overlap");
+    assert!(matches!(err.kind, LoadErrorKind::InvalidOverlap(_)));
+    assert_eq!(err.line_number, 4);
+
+    let cigar = Cigar::parse(b"8M").unwrap();
+    assert!(check_overlap_lengths(&cigar, Some(6), Some(6)).is_err());
+    assert!(check_overlap_lengths(&cigar, Some(8), Some(8)).is_ok());
+    assert!(check_overlap_lengths(&cigar, Some(8), None).is_err());
+}
@@ -0,0 +1,141 @@
+// not every test crate uses every helper
+#![allow(dead_code)]
+
+use handlegraph::{
+    handle::{Edge, Handle},
+    handlegraph::*,
+    pathhandlegraph::*,
+};
+
+use handlegraph_cli::{
+    io::{packed_graph_from_mmap_with_options, LoadOptions, LoadedGraph},
+    load_error::ErrorPolicy,
+    mmap_gfa::MmapGFA,
+};
+
+use anyhow::Result;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+pub fn fixture(name: &str) -> String {
+    format!("{}/tests/gfa/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+/// Loads a fixture with tags kept, without writing a `.gfai` file
+/// next to it.
+pub fn load(name: &str, translate_names: bool) -> Result<LoadedGraph> {
+    load_path(&fixture(name), translate_names)
+}
+
+pub fn load_path(path: &str, translate_names: bool) -> Result<LoadedGraph> {
+    let options = LoadOptions {
+        skip_index_file: true,
+        keep_tags: true,
+        translate_names,
+        ..LoadOptions::default()
+    };
+    let mut mmap_gfa = MmapGFA::new(path)?;
+    packed_graph_from_mmap_with_options(&mut mmap_gfa, &options)
+}
+
+/// Loads a fixture like `load`, skipping bad records instead of
+/// failing.
+pub fn load_lenient(name: &str) -> LoadedGraph {
+    let options = LoadOptions {
+        skip_index_file: true,
+        keep_tags: true,
+        policy: ErrorPolicy::Lenient,
+        ..LoadOptions::default()
+    };
+    let mut mmap_gfa = MmapGFA::new(&fixture(name)).unwrap();
+    packed_graph_from_mmap_with_options(&mut mmap_gfa, &options).unwrap()
+}
+
+/// The overlap of the edge between two oriented segments, e.g. `s1+`.
+pub fn overlap(loaded: &LoadedGraph, from: &str, to: &str) -> Option<String> {
+    let handle = |step: &str| {
+        let (name, orient) = step.split_at(step.len() - 1);
+        let id = loaded.id_mapping.segment_id(name.as_bytes()).unwrap();
+        Handle::pack(id, orient == "-")
+    };
+    loaded
+        .overlaps
+        .get(Edge(handle(from), handle(to)))
+        .map(|cigar| cigar.to_string())
+}
+
+/// The contents of a loaded graph in terms of the segment names of
+/// the input, so graphs can be compared whatever node IDs their
+/// segments were assigned.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GraphSummary {
+    /// The sequence and tags of each segment.
+    pub segments: BTreeMap<String, (String, String)>,
+    /// Edges as `from to`, e.g. `s1+ s2-`, in whichever of their two
+    /// orientations sorts first.
+    pub edges: BTreeSet<String>,
+    /// The steps of each path, e.g. `s1+`.
+    pub paths: BTreeMap<String, Vec<String>>,
+}
+
+impl GraphSummary {
+    pub fn new(loaded: &LoadedGraph) -> Self {
+        let graph = &loaded.graph;
+
+        let name = |handle: Handle| {
+            let mut name = Vec::new();
+            loaded
+                .id_mapping
+                .write_original(&mut name, handle.id())
+                .unwrap();
+            let orient = if handle.is_reverse() { '-' } else { '+' };
+            format!("{}{}", String::from_utf8(name).unwrap(), orient)
+        };
+
+        let segments = graph
+            .handles()
+            .map(|handle| {
+                let mut segment = name(handle);
+                segment.pop();
+                let sequence = graph.sequence_vec(handle);
+                let tags = loaded
+                    .tags
+                    .as_ref()
+                    .and_then(|tags| tags.segment(handle.id()))
+                    .unwrap_or_default();
+                let sequence = String::from_utf8(sequence).unwrap();
+                let tags = String::from_utf8(tags.to_vec()).unwrap();
+                (segment, (sequence, tags))
+            })
+            .collect();
+
+        let edges = graph
+            .edges()
+            .map(|Edge(from, to)| {
+                let edge = format!("{} {}", name(from), name(to));
+                let reversed =
+                    format!("{} {}", name(to.flip()), name(from.flip()));
+                edge.min(reversed)
+            })
+            .collect();
+
+        let paths = graph
+            .path_ids()
+            .map(|path_id| {
+                let path_name = graph.get_path_name_vec(path_id).unwrap();
+                let steps = graph
+                    .path_steps(path_id)
+                    .unwrap()
+                    .map(|step| name(step.handle()))
+                    .collect();
+                (String::from_utf8(path_name).unwrap(), steps)
+            })
+            .collect();
+
+        GraphSummary {
+            segments,
+            edges,
+            paths,
+        }
+    }
+}
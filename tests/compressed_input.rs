@@ -0,0 +1,179 @@
+mod common;
+
+use common::{fixture, load, load_path, GraphSummary};
+
+use handlegraph_cli::{
+    io::{packed_graph_from_mmap_with_options, LoadOptions},
+    load_error::{LoadError, LoadErrorKind},
+    mmap_gfa::MmapGFA,
+};
+
+use flate2::{
+    write::{DeflateEncoder, GzEncoder},
+    Compression, Crc,
+};
+
+use std::io::Write;
+use std::path::Path;
+
+/// Compresses `data` as a single BGZF block.
+fn bgzf_block(data: &[u8]) -> Vec<u8> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let mut crc = Crc::new();
+    crc.update(data);
+
+    let bsize = (18 + compressed.len() + 8 - 1) as u16;
+    let mut block = vec![0x1f, 0x8b, 8, 4, 0, 0, 0, 0, 0, 0xff, 6, 0];
+    block.extend_from_slice(b"BC");
+    block.extend_from_slice(&2u16.to_le_bytes());
+    block.extend_from_slice(&bsize.to_le_bytes());
+    block.extend_from_slice(&compressed);
+    block.extend_from_slice(&crc.sum().to_le_bytes());
+    block.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    block
+}
+
+/// Writes the fixture as BGZF with blocks of `block_size` bytes, and
+/// optionally a `.gzi` index with the end of every block, like
+/// `bgzip -i`.
+fn write_bgzf(name: &str, path: &Path, block_size: usize, gzi: bool) {
+    let data = std::fs::read(fixture(name)).unwrap();
+
+    let mut bgzf = Vec::new();
+    let mut index = Vec::new();
+    for (ix, chunk) in data.chunks(block_size).enumerate() {
+        bgzf.extend(bgzf_block(chunk));
+        let data_end = (ix * block_size + chunk.len()) as u64;
+        index.push((bgzf.len() as u64, data_end));
+    }
+    bgzf.extend(bgzf_block(&[]));
+    std::fs::write(path, bgzf).unwrap();
+
+    if gzi {
+        let mut out = (index.len() as u64).to_le_bytes().to_vec();
+        for (offset, data_offset) in index {
+            out.extend_from_slice(&offset.to_le_bytes());
+            out.extend_from_slice(&data_offset.to_le_bytes());
+        }
+        std::fs::write(format!("{}.gzi", path.display()), out).unwrap();
+    }
+}
+
+const FIXTURES: [&str; 3] = [
+    "order_sorted.gfa",
+    "line_endings_mixed.gfa",
+    "line_endings_no_final_newline.gfa",
+];
+
+#[test]
+fn bgzf_loads_like_uncompressed() {
+    let dir = tempfile::tempdir().unwrap();
+
+    for &name in FIXTURES.iter() {
+        let expected = GraphSummary::new(&load(name, false).unwrap());
+
+        // small blocks split lines across several blocks
+        for &block_size in [5, 64, 65280].iter() {
+            for &gzi in [false, true].iter() {
+                let path = dir
+                    .path()
+                    .join(format!("{}.{}.{}.gz", name, block_size, gzi));
+                write_bgzf(name, &path, block_size, gzi);
+
+                let loaded = load_path(path.to_str().unwrap(), false).unwrap();
+                let case = format!("{} {} {}", name, block_size, gzi);
+                assert_eq!(GraphSummary::new(&loaded), expected, "{}", case);
+            }
+        }
+    }
+}
+
+#[test]
+fn gzip_loads_like_uncompressed() {
+    let dir = tempfile::tempdir().unwrap();
+
+    for &name in FIXTURES.iter() {
+        let expected = GraphSummary::new(&load(name, false).unwrap());
+
+        let data = std::fs::read(fixture(name)).unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&data).unwrap();
+        let path = dir.path().join(format!("{}.gz", name));
+        std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+
+        let loaded = load_path(path.to_str().unwrap(), false).unwrap();
+        assert_eq!(GraphSummary::new(&loaded), expected, "{}", name);
+    }
+}
+
+#[test]
+fn bgzf_errors_have_line_numbers() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("order_missing_segment.gfa.gz");
+    write_bgzf("order_missing_segment.gfa", &path, 16, false);
+
+    let err = match load_path(path.to_str().unwrap(), false) {
+        Ok(_) => panic!("loaded {}", path.display()),
+        Err(err) => err,
+    };
+    let err = err.downcast_ref::<LoadError>().unwrap();
+
+    assert_eq!(err.kind, LoadErrorKind::MissingSegment);
+    assert_eq!(err.line_number, 2);
+}
+
+#[test]
+fn corrupt_bgzf_block_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("order_sorted.gfa.gz");
+    let path_str = path.to_str().unwrap();
+    write_bgzf("order_sorted.gfa", &path, 16, false);
+
+    // save a line index of the intact file
+    MmapGFA::new(path_str)
+        .unwrap()
+        .load_or_build_index()
+        .unwrap();
+
+    // corrupt the compressed data of the second block, keeping the
+    // length and modification time of the file so the index is used
+    let mtime = std::fs::metadata(&path).unwrap().modified().unwrap();
+    let mut bytes = std::fs::read(&path).unwrap();
+    let data = std::fs::read(fixture("order_sorted.gfa")).unwrap();
+    let first_len = bgzf_block(&data[..16]).len();
+    let second_len = bgzf_block(&data[16..32]).len();
+    for byte in &mut bytes[first_len + 18..first_len + second_len - 8] {
+        *byte = 0xff;
+    }
+    std::fs::write(&path, bytes).unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(mtime)
+        .unwrap();
+
+    for &skip_index_file in [false, true].iter() {
+        let options = LoadOptions {
+            skip_index_file,
+            ..LoadOptions::default()
+        };
+        let mut mmap_gfa = MmapGFA::new(path_str).unwrap();
+        let err = match packed_graph_from_mmap_with_options(
+            &mut mmap_gfa,
+            &options,
+        ) {
+            Ok(_) => panic!("loaded {}", path.display()),
+            Err(err) => format!("{:#}", err),
+        };
+        assert!(
+            err.contains("Failed to decompress BGZF block"),
+            "{}: {}",
+            skip_index_file,
+            err
+        );
+    }
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::{fixture, load, overlap, GraphSummary};
+
+use handlegraph_cli::{
+    load_error::{LoadError, LoadErrorKind},
+    mmap_gfa::LineType,
+};
+
+#[test]
+fn dovetails_become_edges() {
+    let loaded = load("gfa2_dovetails.gfa", true).unwrap();
+    let summary = GraphSummary::new(&loaded);
+
+    // e5 is an internal alignment, not a dovetail
+    assert_eq!(
+        summary.edges.iter().collect::<Vec<_>>(),
+        ["s1+ s2+", "s1+ s4+", "s2+ s3-", "s3- s4+"]
+    );
+
+    assert_eq!(overlap(&loaded, "s1+", "s2+"), None);
+    assert_eq!(overlap(&loaded, "s2+", "s3-").as_deref(), Some("2M"));
+    assert_eq!(overlap(&loaded, "s3+", "s2-").as_deref(), Some("2M"));
+    assert_eq!(overlap(&loaded, "s3-", "s4+").as_deref(), Some("1M"));
+    assert_eq!(overlap(&loaded, "s1+", "s4+"), None);
+
+    // the edge reference in the ordered group is skipped
+    assert_eq!(summary.paths["o1"], ["s1+", "s2+", "s3-", "s4+"]);
+}
+
+#[test]
+fn group_reference_without_orientation_is_an_error() {
+    let err = match load("gfa2_bad_reference.gfa", true) {
+        Ok(_) => panic!("loaded {}", fixture("gfa2_bad_reference.gfa")),
+        Err(err) => err,
+    };
+    let err = err.downcast_ref::<LoadError>().unwrap();
+
+    assert!(matches!(err.kind, LoadErrorKind::Parse(_)));
+    assert_eq!(err.line_type, LineType::OGroup);
+    assert_eq!(err.line_number, 5);
+    assert_eq!(err.field.as_deref(), Some("references"));
+    assert_eq!(err.value.as_deref(), Some("s2"));
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::fixture;
+
+use handlegraph_cli::{
+    gfai::{read_index, sidecar_path, write_index},
+    mmap_gfa::MmapGFA,
+};
+
+use std::fs::File;
+
+const FIXTURES: [&str; 4] = [
+    "order_sorted.gfa",
+    "walks.gfa",
+    "validate_gfa1.gfa",
+    "gfa2_dovetails.gfa",
+];
+
+/// Copies a fixture into `dir`, so its sidecar isn't written next to
+/// the fixture.
+fn copy_fixture(dir: &tempfile::TempDir, name: &str) -> String {
+    let path = dir.path().join(name);
+    std::fs::copy(fixture(name), &path).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn index_round_trips() {
+    let dir = tempfile::tempdir().unwrap();
+
+    for &name in FIXTURES.iter() {
+        let path = copy_fixture(&dir, name);
+        let mmap_gfa = MmapGFA::new(&path).unwrap();
+        let data_len = mmap_gfa.data().len();
+        let indices = mmap_gfa.build_index().unwrap();
+
+        let index_path = write_index(&path, data_len, &indices).unwrap();
+        assert_eq!(index_path, sidecar_path(&path));
+
+        let read = read_index(&path, data_len).unwrap();
+        assert_eq!(read.as_ref(), Some(&indices), "{}", name);
+
+        // the index was built from the same file
+        assert_eq!(mmap_gfa.load_or_build_index().unwrap(), indices);
+    }
+}
+
+#[test]
+fn stale_index_is_ignored() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = copy_fixture(&dir, "order_sorted.gfa");
+    let mmap_gfa = MmapGFA::new(&path).unwrap();
+    let data_len = mmap_gfa.data().len();
+    let indices = mmap_gfa.build_index().unwrap();
+    write_index(&path, data_len, &indices).unwrap();
+
+    // a different length of the data, e.g. of a compressed file
+    assert_eq!(read_index(&path, data_len + 1).unwrap(), None);
+
+    let mtime = std::time::SystemTime::UNIX_EPOCH;
+    File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(mtime)
+        .unwrap();
+    assert_eq!(read_index(&path, data_len).unwrap(), None);
+}
+
+#[test]
+fn corrupt_index_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = copy_fixture(&dir, "order_sorted.gfa");
+    let mmap_gfa = MmapGFA::new(&path).unwrap();
+    let data_len = mmap_gfa.data().len();
+    let indices = mmap_gfa.build_index().unwrap();
+    let index_path = write_index(&path, data_len, &indices).unwrap();
+
+    let mut bytes = std::fs::read(&index_path).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xff;
+    std::fs::write(&index_path, &bytes).unwrap();
+    let err = read_index(&path, data_len).unwrap_err();
+    assert!(err.to_string().contains("checksum mismatch"), "{}", err);
+
+    std::fs::write(&index_path, &bytes[..20]).unwrap();
+    let err = read_index(&path, data_len).unwrap_err();
+    assert!(err.to_string().contains("truncated"), "{}", err);
+
+    // a corrupt index is rebuilt rather than used
+    assert_eq!(mmap_gfa.load_or_build_index().unwrap(), indices);
+    assert_eq!(read_index(&path, data_len).unwrap(), Some(indices));
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use common::{fixture, GraphSummary};
+
+use handlegraph::handle::{Edge, Handle};
+
+use handlegraph_cli::{
+    gfa_writer::{write_gfa, WriteOptions},
+    io::{packed_graph_from_mmap_with_options, LoadOptions, LoadedGraph},
+    mmap_gfa::MmapGFA,
+};
+
+const FIXTURE: &str = "jumps_containments.gfa";
+
+fn load_jumps(path: &str, jumps: bool) -> LoadedGraph {
+    let options = LoadOptions {
+        skip_index_file: true,
+        keep_tags: true,
+        translate_names: true,
+        jumps,
+        ..LoadOptions::default()
+    };
+    let mut mmap_gfa = MmapGFA::new(path).unwrap();
+    packed_graph_from_mmap_with_options(&mut mmap_gfa, &options).unwrap()
+}
+
+fn handle(loaded: &LoadedGraph, step: &str) -> Handle {
+    let (name, orient) = step.split_at(step.len() - 1);
+    let id = loaded.id_mapping.segment_id(name.as_bytes()).unwrap();
+    Handle::pack(id, orient == "-")
+}
+
+/// The gap of the jump between two oriented segments, if the edge
+/// between them was loaded from a J line.
+fn gap(loaded: &LoadedGraph, from: &str, to: &str) -> Option<String> {
+    let edge = Edge(handle(loaded, from), handle(loaded, to));
+    loaded.jumps.get(edge).map(|gap| gap.to_string())
+}
+
+/// Each containment as `container contained pos overlap tags`.
+fn containments(loaded: &LoadedGraph) -> Vec<String> {
+    let name = |handle: Handle| {
+        let mut name = Vec::new();
+        loaded
+            .id_mapping
+            .write_original(&mut name, handle.id())
+            .unwrap();
+        let orient = if handle.is_reverse() { '-' } else { '+' };
+        format!("{}{}", String::from_utf8(name).unwrap(), orient)
+    };
+    loaded
+        .containments
+        .iter()
+        .map(|c| {
+            let overlap = c.overlap.as_ref().map(|o| o.to_string());
+            let tags = c.tags.as_deref().unwrap_or_default();
+            format!(
+                "{} {} {} {} {}",
+                name(c.container),
+                name(c.contained),
+                c.pos,
+                overlap.as_deref().unwrap_or("*"),
+                String::from_utf8(tags.to_vec()).unwrap()
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn jumps_are_edges_only_when_asked_for() {
+    let loaded = load_jumps(&fixture(FIXTURE), false);
+    assert!(loaded.jumps.is_empty());
+    assert_eq!(GraphSummary::new(&loaded).edges.len(), 1);
+
+    let loaded = load_jumps(&fixture(FIXTURE), true);
+    // the jump from s1 to s3 is dropped in favor of the link
+    assert_eq!(loaded.jumps.len(), 2);
+    assert_eq!(GraphSummary::new(&loaded).edges.len(), 3);
+    assert_eq!(gap(&loaded, "s1+", "s3+"), None);
+    assert_eq!(gap(&loaded, "s3+", "s4-").as_deref(), Some("5"));
+    assert_eq!(gap(&loaded, "s4+", "s3-").as_deref(), Some("5"));
+    assert_eq!(gap(&loaded, "s4+", "s1+").as_deref(), Some("*"));
+}
+
+#[test]
+fn jumps_and_containments_round_trip() {
+    let loaded = load_jumps(&fixture(FIXTURE), true);
+    assert_eq!(
+        containments(&loaded),
+        ["s1+ s2+ 1 4M ID:Z:c1", "s1+ s4- 4 * "]
+    );
+
+    let options = WriteOptions {
+        original_ids: true,
+        walks: false,
+        provenance: None,
+    };
+    let mut gfa = Vec::new();
+    write_gfa(&loaded, &options, &mut gfa).unwrap();
+    let gfa_text = String::from_utf8(gfa.clone()).unwrap();
+    assert!(gfa_text.starts_with("H\tVN:Z:1.2\n"), "{}", gfa_text);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(FIXTURE);
+    std::fs::write(&path, &gfa).unwrap();
+    let reloaded = load_jumps(path.to_str().unwrap(), true);
+
+    assert_eq!(GraphSummary::new(&reloaded), GraphSummary::new(&loaded));
+    assert_eq!(containments(&reloaded), containments(&loaded));
+    assert_eq!(reloaded.jumps.len(), 2);
+    for (from, to) in [("s3+", "s4-"), ("s4+", "s1+"), ("s1+", "s3+")].iter() {
+        assert_eq!(gap(&reloaded, from, to), gap(&loaded, from, to));
+    }
+    let jump_tags = reloaded
+        .tags
+        .as_ref()
+        .and_then(|tags| {
+            tags.link(Edge(handle(&reloaded, "s3+"), handle(&reloaded, "s4-")))
+        })
+        .unwrap();
+    assert_eq!(jump_tags, b"SC:i:1");
+}
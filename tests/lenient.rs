@@ -0,0 +1,61 @@
+mod common;
+
+use common::{fixture, load, load_lenient, GraphSummary};
+
+use handlegraph_cli::{
+    load_error::{
+        rejected_counts, write_rejected_report, LoadError, LoadErrorKind,
+    },
+    mmap_gfa::LineType,
+};
+
+const BAD_RECORDS: &str = "lenient_bad_records.gfa";
+
+#[test]
+fn strict_load_stops_at_first_bad_record() {
+    let err = match load(BAD_RECORDS, false) {
+        Ok(_) => panic!("loaded {}", fixture(BAD_RECORDS)),
+        Err(err) => err,
+    };
+    let err = err.downcast_ref::<LoadError>().unwrap();
+
+    assert_eq!(err.kind, LoadErrorKind::DuplicateSegment);
+    assert_eq!(err.line_number, 4);
+}
+
+#[test]
+fn lenient_load_skips_bad_records() {
+    let loaded = load_lenient(BAD_RECORDS);
+
+    let summary = GraphSummary::new(&loaded);
+    assert_eq!(summary.segments["s1"].0, "ACGT");
+    assert_eq!(summary.segments.len(), 2);
+    assert_eq!(summary.edges.len(), 1);
+    assert_eq!(summary.paths.keys().collect::<Vec<_>>(), ["p1"]);
+
+    assert_eq!(
+        rejected_counts(&loaded.rejected),
+        [
+            (LineType::Segment, 1),
+            (LineType::Link, 2),
+            (LineType::Path, 1)
+        ]
+    );
+}
+
+#[test]
+fn rejected_report_lists_lines_and_offsets() {
+    let loaded = load_lenient(BAD_RECORDS);
+
+    let mut report = Vec::new();
+    write_rejected_report(&loaded.rejected, &mut report).unwrap();
+
+    let expected = "\
+#type\tline\toffset\terror\tfield\tvalue\tmessage
+S\t4\t29\tduplicate_segment\tname\ts1\tduplicate segment name
+L\t6\t52\tmissing_segment\tTo\ts9\tundefined segment
+L\t7\t67\tparse_error\t*\t*\tparse error: Failed to parse an orientation character
+P\t9\t97\tmissing_segment\tSegmentNames\ts7\tundefined segment
+";
+    assert_eq!(String::from_utf8(report).unwrap(), expected);
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::{load, GraphSummary};
+
+const VARIANTS: [&str; 4] = [
+    "line_endings_crlf.gfa",
+    "line_endings_mixed.gfa",
+    "line_endings_no_final_newline.gfa",
+    "line_endings_blank_lines.gfa",
+];
+
+#[test]
+fn line_endings_load_like_lf() {
+    for &translate_names in [false, true].iter() {
+        let expected = load("line_endings_lf.gfa", translate_names).unwrap();
+        let expected = GraphSummary::new(&expected);
+
+        for &name in VARIANTS.iter() {
+            let loaded = load(name, translate_names).unwrap();
+            assert_eq!(GraphSummary::new(&loaded), expected, "{}", name);
+        }
+    }
+}
+
+#[test]
+fn names_have_no_carriage_returns() {
+    for &name in VARIANTS.iter() {
+        let loaded = load(name, true).unwrap();
+        let summary = GraphSummary::new(&loaded);
+
+        assert_eq!(
+            summary.segments.keys().collect::<Vec<_>>(),
+            ["1", "2", "3"],
+            "{}",
+            name
+        );
+        assert_eq!(
+            summary.paths.keys().collect::<Vec<_>>(),
+            ["p1", "p2"],
+            "{}",
+            name
+        );
+        for header in loaded.headers.iter() {
+            assert!(!header.ends_with(b"\r"), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use common::{fixture, load, load_lenient, GraphSummary};
+
+use handlegraph_cli::{
+    load_error::{LoadError, LoadErrorKind},
+    mmap_gfa::LineType,
+};
+
+#[test]
+fn shuffled_orders_load_like_sorted() {
+    let expected = GraphSummary::new(&load("order_sorted.gfa", false).unwrap());
+    assert_eq!(expected.segments.len(), 4);
+    assert_eq!(expected.edges.len(), 3);
+    assert_eq!(expected.paths.len(), 2);
+
+    for &name in ["order_paths_first.gfa", "order_interleaved.gfa"].iter() {
+        let loaded = load(name, false).unwrap();
+        assert_eq!(GraphSummary::new(&loaded), expected, "{}", name);
+    }
+}
+
+#[test]
+fn shuffled_numeric_ids_load_like_sorted() {
+    // the same graph as order_sorted.gfa, with segment `sN` named `N`
+    // and without the walk
+    let sorted = GraphSummary::new(&load("order_sorted.gfa", false).unwrap());
+    let renamed = |name: &String| name.replace('s', "");
+    let expected = GraphSummary {
+        segments: sorted
+            .segments
+            .iter()
+            .map(|(name, segment)| (renamed(name), segment.clone()))
+            .collect(),
+        edges: sorted.edges.iter().map(renamed).collect(),
+        paths: sorted
+            .paths
+            .iter()
+            .filter(|(name, _)| name.as_str() == "p1")
+            .map(|(name, steps)| {
+                (name.clone(), steps.iter().map(renamed).collect())
+            })
+            .collect(),
+    };
+
+    let loaded = load("order_numeric_shuffled.gfa", false).unwrap();
+    assert_eq!(GraphSummary::new(&loaded), expected);
+}
+
+#[test]
+fn missing_segment_is_reported_on_its_line() {
+    let err = match load("order_missing_segment.gfa", false) {
+        Ok(_) => panic!("loaded {}", fixture("order_missing_segment.gfa")),
+        Err(err) => err,
+    };
+    let err = err.downcast_ref::<LoadError>().unwrap();
+
+    assert_eq!(err.kind, LoadErrorKind::MissingSegment);
+    assert_eq!(err.line_type, LineType::Path);
+    assert_eq!(err.line_number, 2);
+    assert_eq!(err.value.as_deref(), Some("s5"));
+}
+
+#[test]
+fn path_of_undefined_segments_is_reported_in_any_order() {
+    let name = "order_undefined_path.gfa";
+    let err = match load(name, false) {
+        Ok(_) => panic!("loaded {}", fixture(name)),
+        Err(err) => err,
+    };
+    let err = err.downcast_ref::<LoadError>().unwrap();
+
+    assert_eq!(err.kind, LoadErrorKind::MissingSegment);
+    assert_eq!(err.line_type, LineType::Path);
+    assert_eq!(err.line_number, 4);
+    assert_eq!(err.value.as_deref(), Some("s8"));
+
+    // skipping the path leaves the sorted graph
+    let expected = GraphSummary::new(&load("order_sorted.gfa", false).unwrap());
+    let loaded = load_lenient(name);
+    assert_eq!(GraphSummary::new(&loaded), expected);
+    assert_eq!(loaded.rejected.len(), 1);
+    assert_eq!(loaded.rejected[0].line_number, 4);
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use handlegraph::handlegraph::*;
+
+use handlegraph_cli::{
+    io::{
+        packed_graph_from_mmap_with_options, LoadOptions, LoadedGraph,
+        MissingSequences,
+    },
+    load_error::LoadError,
+    mmap_gfa::MmapGFA,
+};
+
+use anyhow::Result;
+
+use common::fixture;
+
+const MISSING: &str = "missing_sequences.gfa";
+
+fn load_missing(missing_sequences: MissingSequences) -> Result<LoadedGraph> {
+    let options = LoadOptions {
+        skip_index_file: true,
+        missing_sequences,
+        ..LoadOptions::default()
+    };
+    let mut mmap_gfa = MmapGFA::new(&fixture(MISSING))?;
+    packed_graph_from_mmap_with_options(&mut mmap_gfa, &options)
+}
+
+#[test]
+fn literal_segments_count_by_their_length_tag() {
+    let loaded = load_missing(MissingSequences::Literal).unwrap();
+
+    // s2 and s3 each hold a single N; s2 counts as its LN of 5 and
+    // s3, which has no LN, as the one base it holds
+    assert_eq!(loaded.graph.total_length(), 4 + 1 + 1 + 2);
+    assert_eq!(loaded.total_length(), 4 + 5 + 1 + 2);
+    assert_eq!(loaded.literal.len(), 2);
+}
+
+#[test]
+fn placeholder_needs_a_length_tag() {
+    let err = match load_missing(MissingSequences::LengthOnly) {
+        Ok(_) => panic!("loaded {}", fixture(MISSING)),
+        Err(err) => err,
+    };
+    let err = err.downcast_ref::<LoadError>().unwrap();
+
+    assert_eq!(err.line_number, 4);
+    assert_eq!(err.field.as_deref(), Some("LN"));
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::{load, load_path, overlap};
+
+use handlegraph_cli::{
+    gfa_writer::{write_gfa, WriteOptions},
+    overlaps::{parse_link_overlap, Cigar, CigarOp},
+};
+
+#[test]
+fn cigars_parse_into_operations() {
+    let cigar = Cigar::parse(b"2M1D1I3=1X").unwrap();
+    assert_eq!(
+        cigar.ops(),
+        [
+            (2, CigarOp::Match),
+            (1, CigarOp::Deletion),
+            (1, CigarOp::Insertion),
+            (3, CigarOp::SeqMatch),
+            (1, CigarOp::SeqMismatch),
+        ]
+    );
+    assert_eq!(cigar.from_len(), 7);
+    assert_eq!(cigar.to_len(), 7);
+    assert!(!cigar.is_ungapped());
+    assert_eq!(cigar.to_string(), "2M1D1I3=1X");
+    assert_eq!(cigar.reversed().to_string(), "1X3=1D1I2M");
+
+    assert!(Cigar::parse(b"0M").unwrap().is_blunt());
+    for &bad in [&b""[..], b"*", b"M", b"3", b"3Q", b"2M3"].iter() {
+        assert_eq!(Cigar::parse(bad), None, "{:?}", bad);
+    }
+
+    assert_eq!(parse_link_overlap(b"*", None, None), Ok(None));
+    assert_eq!(parse_link_overlap(b"0M", Some(2), Some(2)), Ok(None));
+    assert!(parse_link_overlap(b"3Q", Some(4), Some(4)).is_err());
+}
+
+#[test]
+fn link_overlaps_are_kept_and_written_back() {
+    let loaded = load("overlaps.gfa", true).unwrap();
+    assert_eq!(loaded.overlaps.len(), 3);
+
+    assert_eq!(overlap(&loaded, "s1+", "s2+").as_deref(), Some("3M"));
+    assert_eq!(overlap(&loaded, "s2+", "s3+").as_deref(), Some("1=1X"));
+    assert_eq!(overlap(&loaded, "s3+", "s4+").as_deref(), Some("2M1D1M"));
+    // the same link traversed the other way
+    assert_eq!(overlap(&loaded, "s4-", "s3-").as_deref(), Some("1M1I2M"));
+    // blunt and missing overlaps aren't stored
+    assert_eq!(overlap(&loaded, "s4-", "s1-"), None);
+    assert_eq!(overlap(&loaded, "s1+", "s3-"), None);
+
+    let options = WriteOptions {
+        original_ids: true,
+        walks: false,
+        provenance: None,
+    };
+    let mut gfa = Vec::new();
+    write_gfa(&loaded, &options, &mut gfa).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("overlaps.gfa");
+    std::fs::write(&path, &gfa).unwrap();
+    let reloaded = load_path(path.to_str().unwrap(), true).unwrap();
+
+    for (from, to) in [("s1+", "s2+"), ("s2+", "s3+"), ("s3+", "s4+")].iter() {
+        assert_eq!(overlap(&reloaded, from, to), overlap(&loaded, from, to));
+    }
+    assert_eq!(reloaded.overlaps.len(), 3);
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::{fixture, load, load_lenient, GraphSummary};
+
+use handlegraph_cli::{
+    load_error::{LoadError, LoadErrorKind},
+    mmap_gfa::LineType,
+};
+
+#[test]
+fn path_without_segments_fails_strict_load() {
+    let name = "path_missing_segments.gfa";
+    let err = match load(name, false) {
+        Ok(_) => panic!("loaded {}", fixture(name)),
+        Err(err) => err,
+    };
+    let err = err.downcast_ref::<LoadError>().unwrap();
+
+    assert_eq!(err.kind, LoadErrorKind::MissingSegment);
+    assert_eq!(err.line_type, LineType::Path);
+    assert_eq!(err.line_number, 6);
+    assert_eq!(err.value.as_deref(), Some("s7"));
+}
+
+#[test]
+fn path_without_segments_is_rejected_by_lenient_load() {
+    let loaded = load_lenient("path_missing_segments.gfa");
+
+    let rejected = loaded
+        .rejected
+        .iter()
+        .map(|err| (err.kind.clone(), err.line_number, err.value.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rejected,
+        [
+            (LoadErrorKind::MissingSegment, 6, Some("s7")),
+            (LoadErrorKind::MissingSegment, 7, Some("s8")),
+        ]
+    );
+
+    let summary = GraphSummary::new(&loaded);
+    assert_eq!(summary.segments.len(), 2);
+    assert_eq!(summary.paths.len(), 1);
+    assert_eq!(summary.paths["p1"], ["s1+", "s2+"]);
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::{fixture, load, load_path, GraphSummary};
+
+use flate2::{write::GzEncoder, Compression};
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const FIXTURE: &str = "order_sorted.gfa";
+
+/// Runs `stats` on `input` with `stdin` piped in, and returns its
+/// output without the first line, which names the input.
+fn stats(input: &str, stdin: &[u8]) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_handlegraph-cli"))
+        .args(["stats", input])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "stats {} failed", input);
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    stdout.lines().skip(1).collect::<Vec<_>>().join("\n")
+}
+
+#[test]
+fn stdin_loads_like_a_file() {
+    let data = std::fs::read(fixture(FIXTURE)).unwrap();
+    let expected = stats(&fixture(FIXTURE), b"");
+    assert!(expected.contains("nodes:  4"), "{}", expected);
+
+    assert_eq!(stats("-", &data), expected);
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&data).unwrap();
+    assert_eq!(stats("-", &encoder.finish().unwrap()), expected);
+}
+
+#[test]
+fn named_pipe_loads_like_a_file() {
+    let expected = GraphSummary::new(&load(FIXTURE, false).unwrap());
+
+    let dir = tempfile::tempdir().unwrap();
+    let pipe = dir.path().join("graph.gfa");
+    let status = Command::new("mkfifo").arg(&pipe).status().unwrap();
+    assert!(status.success());
+
+    let writer = {
+        let pipe = pipe.clone();
+        std::thread::spawn(move || {
+            let data = std::fs::read(fixture(FIXTURE)).unwrap();
+            std::fs::write(pipe, data).unwrap();
+        })
+    };
+    let loaded = load_path(pipe.to_str().unwrap(), false).unwrap();
+    writer.join().unwrap();
+
+    assert_eq!(GraphSummary::new(&loaded), expected);
+}
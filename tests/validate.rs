@@ -0,0 +1,81 @@
+mod common;
+
+use common::fixture;
+
+use handlegraph_cli::{
+    mmap_gfa::MmapGFA,
+    validation::{validate_gfa, Validation},
+};
+
+fn validate(name: &str) -> Validation {
+    let mmap_gfa = MmapGFA::new(&fixture(name)).unwrap();
+    let indices = mmap_gfa.build_index().unwrap();
+    validate_gfa(&mmap_gfa, &indices).unwrap()
+}
+
+/// The line number, error code, field, and value of each error.
+fn error_list(validation: &Validation) -> Vec<(usize, &str, &str, &str)> {
+    validation
+        .errors
+        .iter()
+        .map(|err| {
+            (
+                err.line_number,
+                err.kind.code(),
+                err.field.as_deref().unwrap_or("*"),
+                err.value.as_deref().unwrap_or("*"),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn valid_gfa_has_no_errors() {
+    for &name in ["order_sorted.gfa", "walks.gfa"].iter() {
+        let validation = validate(name);
+        assert!(validation.is_valid(), "{}: {:?}", name, validation.errors);
+    }
+}
+
+#[test]
+fn gfa1_errors_are_reported_on_their_lines() {
+    let validation = validate("validate_gfa1.gfa");
+    assert_eq!(validation.counts.segments, 5);
+    assert_eq!(validation.counts.links, 6);
+    assert_eq!(validation.counts.paths, 7);
+
+    assert_eq!(
+        error_list(&validation),
+        [
+            (6, "duplicate_segment", "name", "s1"),
+            (7, "invalid_sequence", "sequence", "X at position 2"),
+            (10, "missing_segment", "To", "s9"),
+            (11, "non_blunt_overlap", "Overlap", "2M"),
+            (12, "invalid_overlap", "Overlap", "9M"),
+            (13, "invalid_overlap", "Overlap", "3Q"),
+            (15, "duplicate_path", "name", "p1"),
+            (16, "missing_segment", "steps", "s8"),
+            (17, "missing_link", "steps", "s1+,s3+"),
+            (18, "parse_error", "steps", "s2"),
+            (19, "missing_segment", "steps", "s7"),
+            (20, "parse_error", "steps", "1"),
+            (20, "missing_segment", "steps", "2"),
+        ]
+    );
+}
+
+#[test]
+fn gfa2_overlaps_are_reported_like_gfa1() {
+    let validation = validate("validate_gfa2.gfa");
+
+    assert_eq!(
+        error_list(&validation),
+        [
+            (7, "non_blunt_overlap", "eid", "e2"),
+            (8, "invalid_overlap", "eid", "e3"),
+            (9, "missing_segment", "sid2", "s9"),
+            (11, "missing_segment", "references", "s8"),
+            (12, "parse_error", "references", "s2"),
+        ]
+    );
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::{fixture, load, load_lenient, load_path, GraphSummary};
+
+use handlegraph_cli::{
+    gfa_writer::{write_gfa, WriteOptions},
+    load_error::{LoadError, LoadErrorKind},
+    mmap_gfa::LineType,
+};
+
+#[test]
+fn walks_round_trip() {
+    let loaded = load("walks.gfa", false).unwrap();
+    let summary = GraphSummary::new(&loaded);
+    assert_eq!(
+        summary.paths.keys().collect::<Vec<_>>(),
+        ["HG002#1#chr1:0-10", "HG002#2#chr1", "grch38#0#chr1:5-15"]
+    );
+    assert_eq!(summary.paths["HG002#2#chr1"], ["s3+", "s2-"]);
+
+    let options = WriteOptions {
+        original_ids: true,
+        walks: true,
+        provenance: None,
+    };
+    let mut gfa = Vec::new();
+    write_gfa(&loaded, &options, &mut gfa).unwrap();
+
+    // walks without a range are written with the range of the
+    // whole path
+    let walks = String::from_utf8(gfa.clone())
+        .unwrap()
+        .lines()
+        .filter(|line| line.starts_with('W'))
+        .map(String::from)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        walks,
+        [
+            "W\tHG002\t1\tchr1\t0\t10\t>s1>s2<s3",
+            "W\tHG002\t2\tchr1\t0\t6\t>s3<s2",
+            "W\tgrch38\t0\tchr1\t5\t15\t>s1>s2",
+        ]
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("walks.gfa");
+    std::fs::write(&path, gfa).unwrap();
+    let reloaded = load_path(path.to_str().unwrap(), false).unwrap();
+
+    let mut expected = summary;
+    let steps = expected.paths.remove("HG002#2#chr1").unwrap();
+    expected.paths.insert("HG002#2#chr1:0-6".to_string(), steps);
+    assert_eq!(GraphSummary::new(&reloaded), expected);
+}
+
+#[test]
+fn walk_step_without_orientation_is_reported() {
+    let name = "walk_bad_step.gfa";
+    let err = match load(name, false) {
+        Ok(_) => panic!("loaded {}", fixture(name)),
+        Err(err) => err,
+    };
+    let err = err.downcast_ref::<LoadError>().unwrap();
+
+    assert!(matches!(err.kind, LoadErrorKind::Parse(_)), "{}", err);
+    assert_eq!(err.line_type, LineType::Walk);
+    assert_eq!(err.line_number, 5);
+    assert_eq!(err.value.as_deref(), Some("1"));
+
+    let loaded = load_lenient(name);
+    assert_eq!(loaded.rejected.len(), 1);
+    assert_eq!(GraphSummary::new(&loaded).paths.len(), 1);
+}